@@ -85,17 +85,21 @@
 use crate::fx::FxHashMap;
 
 use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::collections::hash_map::Entry;
 use std::convert::Into;
 use std::error::Error;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use measureme::{EventId, EventIdBuilder, Profiler, SerializableString, StringId};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use rustc_serialize::json::{Json, ToJson};
 
 bitflags::bitflags! {
     struct EventFilter: u32 {
@@ -155,6 +159,12 @@ pub struct SelfProfilerRef {
 
     // Print extra verbose generic activities to stdout
     print_extra_verbose_generic_activities: bool,
+
+    // Record verbose and extra verbose generic activities for `-Z time-passes-json`
+    time_passes_recorder: Option<Arc<TimePassesRecorder>>,
+
+    // Aggregate query events for `-Z self-profile-summary`
+    query_summary: Option<Arc<QuerySummary>>,
 }
 
 impl SelfProfilerRef {
@@ -162,6 +172,8 @@ pub fn new(
         profiler: Option<Arc<SelfProfiler>>,
         print_verbose_generic_activities: bool,
         print_extra_verbose_generic_activities: bool,
+        time_passes_recorder: Option<Arc<TimePassesRecorder>>,
+        query_summary: Option<Arc<QuerySummary>>,
     ) -> SelfProfilerRef {
         // If there is no SelfProfiler then the filter mask is set to NONE,
         // ensuring that nothing ever tries to actually access it.
@@ -173,6 +185,33 @@ pub fn new(
             event_filter_mask,
             print_verbose_generic_activities,
             print_extra_verbose_generic_activities,
+            time_passes_recorder,
+            query_summary,
+        }
+    }
+
+    // Like `exec`, this keeps the check for `-Z self-profile-summary` inlined
+    // and everything past it out of the fast path.
+    #[inline(always)]
+    fn with_query_summary<'a>(
+        &'a self,
+        guard: TimingGuard<'a>,
+        event: QuerySummaryEvent,
+    ) -> TimingGuard<'a> {
+        #[inline(never)]
+        fn cold_call<'a>(
+            summary: &'a QuerySummary,
+            mut guard: TimingGuard<'a>,
+            event: QuerySummaryEvent,
+        ) -> TimingGuard<'a> {
+            guard.1 = Some(QuerySummaryTiming::start(summary, event));
+            guard
+        }
+
+        if unlikely!(self.query_summary.is_some()) {
+            cold_call(self.query_summary.as_deref().unwrap(), guard, event)
+        } else {
+            guard
         }
     }
 
@@ -205,7 +244,8 @@ fn cold_call<F>(profiler_ref: &SelfProfilerRef, f: F) -> TimingGuard<'_>
     /// Start profiling a verbose generic activity. Profiling continues until the
     /// VerboseTimingGuard returned from this call is dropped. In addition to recording
     /// a measureme event, "verbose" generic activities also print a timing entry to
-    /// stdout if the compiler is invoked with -Ztime or -Ztime-passes.
+    /// stdout if the compiler is invoked with -Ztime or -Ztime-passes, and are recorded
+    /// if it is invoked with -Ztime-passes-json.
     pub fn verbose_generic_activity<'a>(
         &'a self,
         event_label: &'static str,
@@ -214,12 +254,14 @@ pub fn verbose_generic_activity<'a>(
             if self.print_verbose_generic_activities { Some(event_label.to_owned()) } else { None };
 
         VerboseTimingGuard::start(message, self.generic_activity(event_label))
+            .recorded_by(self.time_passes_recorder.as_deref(), || event_label.to_owned())
     }
 
     /// Start profiling a extra verbose generic activity. Profiling continues until the
     /// VerboseTimingGuard returned from this call is dropped. In addition to recording
     /// a measureme event, "extra verbose" generic activities also print a timing entry to
-    /// stdout if the compiler is invoked with -Ztime-passes.
+    /// stdout if the compiler is invoked with -Ztime-passes, and are recorded if it is
+    /// invoked with -Ztime-passes-json.
     pub fn extra_verbose_generic_activity<'a, A>(
         &'a self,
         event_label: &'static str,
@@ -228,13 +270,30 @@ pub fn extra_verbose_generic_activity<'a, A>(
     where
         A: Borrow<str> + Into<String>,
     {
-        let message = if self.print_extra_verbose_generic_activities {
-            Some(format!("{}({})", event_label, event_arg.borrow()))
-        } else {
-            None
-        };
+        let label = || format!("{}({})", event_label, event_arg.borrow());
+        let message =
+            if self.print_extra_verbose_generic_activities { Some(label()) } else { None };
+        let recorded_label = self.time_passes_recorder.as_ref().map(|_| label());
 
         VerboseTimingGuard::start(message, self.generic_activity_with_arg(event_label, event_arg))
+            .recorded_by(self.time_passes_recorder.as_deref(), || recorded_label.unwrap())
+    }
+
+    /// Sets the name of the crate the activities recorded for `-Z time-passes-json`
+    /// belong to, once it is known.
+    pub fn set_time_passes_crate_name(&self, crate_name: &str) {
+        if let Some(recorder) = &self.time_passes_recorder {
+            *recorder.crate_name.lock() = Some(crate_name.to_owned());
+        }
+    }
+
+    /// Writes the activities recorded for `-Z time-passes-json` so far to the
+    /// file given to the recorder, if any.
+    pub fn write_time_passes_json(&self) -> io::Result<()> {
+        match &self.time_passes_recorder {
+            Some(recorder) => recorder.write(),
+            None => Ok(()),
+        }
     }
 
     /// Start profiling a generic activity. Profiling continues until the
@@ -298,14 +357,19 @@ pub fn generic_activity_with_args(
     /// TimingGuard returned from this call is dropped.
     #[inline(always)]
     pub fn query_provider(&self) -> TimingGuard<'_> {
-        self.exec(EventFilter::QUERY_PROVIDERS, |profiler| {
+        let guard = self.exec(EventFilter::QUERY_PROVIDERS, |profiler| {
             TimingGuard::start(profiler, profiler.query_event_kind, EventId::INVALID)
-        })
+        });
+        self.with_query_summary(guard, QuerySummaryEvent::Provider)
     }
 
     /// Record a query in-memory cache hit.
     #[inline(always)]
     pub fn query_cache_hit(&self, query_invocation_id: QueryInvocationId) {
+        if unlikely!(self.query_summary.is_some()) {
+            let summary = self.query_summary.as_deref().unwrap();
+            cold_path(|| summary.record_cache_hit(&query_invocation_id));
+        }
         self.instant_query_event(
             |profiler| profiler.query_cache_hit_event_kind,
             query_invocation_id,
@@ -328,13 +392,14 @@ pub fn query_blocked(&self) -> TimingGuard<'_> {
     /// TimingGuard returned from this call is dropped.
     #[inline(always)]
     pub fn incr_cache_loading(&self) -> TimingGuard<'_> {
-        self.exec(EventFilter::INCR_CACHE_LOADS, |profiler| {
+        let guard = self.exec(EventFilter::INCR_CACHE_LOADS, |profiler| {
             TimingGuard::start(
                 profiler,
                 profiler.incremental_load_result_event_kind,
                 EventId::INVALID,
             )
-        })
+        });
+        self.with_query_summary(guard, QuerySummaryEvent::IncrCacheLoad)
     }
 
     #[inline(always)]
@@ -373,6 +438,13 @@ pub fn enabled(&self) -> bool {
     pub fn llvm_recording_enabled(&self) -> bool {
         self.event_filter_mask.contains(EventFilter::LLVM)
     }
+
+    /// Returns the query events aggregated for `-Z self-profile-summary`, if enabled.
+    #[inline]
+    pub fn query_summary(&self) -> Option<&QuerySummary> {
+        self.query_summary.as_deref()
+    }
+
     #[inline]
     pub fn get_self_profiler(&self) -> Option<Arc<SelfProfiler>> {
         self.profiler.clone()
@@ -514,7 +586,7 @@ pub fn event_id_builder(&self) -> EventIdBuilder<'_> {
 }
 
 #[must_use]
-pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>);
+pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>, Option<QuerySummaryTiming<'a>>);
 
 impl<'a> TimingGuard<'a> {
     #[inline]
@@ -527,7 +599,7 @@ pub fn start(
         let raw_profiler = &profiler.profiler;
         let timing_guard =
             raw_profiler.start_recording_interval_event(event_kind, event_id, thread_id);
-        TimingGuard(Some(timing_guard))
+        TimingGuard(Some(timing_guard), None)
     }
 
     #[inline]
@@ -539,11 +611,14 @@ pub fn finish_with_query_invocation_id(self, query_invocation_id: QueryInvocatio
                 guard.finish_with_override_event_id(event_id);
             });
         }
+        if let Some(mut timing) = self.1 {
+            timing.query_invocation_id = Some(query_invocation_id);
+        }
     }
 
     #[inline]
     pub fn none() -> TimingGuard<'a> {
-        TimingGuard(None)
+        TimingGuard(None, None)
     }
 
     #[inline(always)]
@@ -556,6 +631,7 @@ pub fn run<R>(self, f: impl FnOnce() -> R) -> R {
 #[must_use]
 pub struct VerboseTimingGuard<'a> {
     start_and_message: Option<(Instant, Option<usize>, String)>,
+    recorded_entry: Option<(&'a TimePassesRecorder, usize)>,
     _guard: TimingGuard<'a>,
 }
 
@@ -564,9 +640,20 @@ pub fn start(message: Option<String>, _guard: TimingGuard<'a>) -> Self {
         VerboseTimingGuard {
             _guard,
             start_and_message: message.map(|msg| (Instant::now(), get_resident_set_size(), msg)),
+            recorded_entry: None,
         }
     }
 
+    /// Records the activity timed by this guard in `recorder`, if there is one.
+    pub fn recorded_by(
+        mut self,
+        recorder: Option<&'a TimePassesRecorder>,
+        label: impl FnOnce() -> String,
+    ) -> Self {
+        self.recorded_entry = recorder.map(|recorder| (recorder, recorder.start_entry(label())));
+        self
+    }
+
     #[inline(always)]
     pub fn run<R>(self, f: impl FnOnce() -> R) -> R {
         let _timer = self;
@@ -580,6 +667,9 @@ fn drop(&mut self) {
             let end_rss = get_resident_set_size();
             print_time_passes_entry(&message[..], start_time.elapsed(), start_rss, end_rss);
         }
+        if let Some((recorder, index)) = self.recorded_entry {
+            recorder.end_entry(index);
+        }
     }
 }
 
@@ -617,6 +707,293 @@ pub fn duration_to_secs_str(dur: std::time::Duration) -> String {
     format!("{:.3}", dur.as_secs_f64())
 }
 
+thread_local! {
+    /// The indices of the `-Z time-passes-json` entries that are in progress on
+    /// the current thread, innermost last.
+    static OPEN_TIME_PASSES_ENTRIES: RefCell<Vec<usize>> = RefCell::new(Vec::new());
+}
+
+struct TimePassesEntry {
+    label: String,
+    /// The entry that was in progress on the same thread when this one started.
+    parent: Option<usize>,
+    /// The time since the start of the recording at which this entry started.
+    start: Duration,
+    /// The wall time of the entry, or `None` if it is still in progress.
+    duration: Option<Duration>,
+    start_rss: Option<usize>,
+    end_rss: Option<usize>,
+}
+
+/// Records the verbose generic activities of a session as a tree and writes it
+/// to a file as JSON, for `-Z time-passes-json`.
+///
+/// An activity is a child of the activity that was in progress on the same
+/// thread when it started. Activities started on threads that aren't running
+/// any other activity, like the LLVM worker threads, are roots of the tree.
+pub struct TimePassesRecorder {
+    path: PathBuf,
+    start_time: Instant,
+    crate_name: Mutex<Option<String>>,
+    entries: Mutex<Vec<TimePassesEntry>>,
+}
+
+impl TimePassesRecorder {
+    pub fn new(path: PathBuf) -> TimePassesRecorder {
+        TimePassesRecorder {
+            path,
+            start_time: Instant::now(),
+            crate_name: Mutex::new(None),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn start_entry(&self, label: String) -> usize {
+        let start_rss = get_resident_set_size();
+        OPEN_TIME_PASSES_ENTRIES.with(|open_entries| {
+            let mut open_entries = open_entries.borrow_mut();
+            let mut entries = self.entries.lock();
+            let index = entries.len();
+            entries.push(TimePassesEntry {
+                label,
+                parent: open_entries.last().copied(),
+                start: self.start_time.elapsed(),
+                duration: None,
+                start_rss,
+                end_rss: None,
+            });
+            open_entries.push(index);
+            index
+        })
+    }
+
+    fn end_entry(&self, index: usize) {
+        let end_rss = get_resident_set_size();
+        let end = self.start_time.elapsed();
+        OPEN_TIME_PASSES_ENTRIES.with(|open_entries| {
+            let mut open_entries = open_entries.borrow_mut();
+            if let Some(position) = open_entries.iter().rposition(|&open| open == index) {
+                open_entries.remove(position);
+            }
+        });
+        let mut entries = self.entries.lock();
+        let entry = &mut entries[index];
+        entry.duration = Some(end - entry.start);
+        entry.end_rss = end_rss;
+    }
+
+    /// Returns the recorded entries as a JSON tree.
+    pub fn to_json(&self) -> Json {
+        let crate_name = self.crate_name.lock().clone();
+        let entries = self.entries.lock();
+
+        // Entries are recorded in the order they started, so parents always
+        // come before their children.
+        let mut children = vec![Vec::new(); entries.len()];
+        let mut roots = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            match entry.parent {
+                Some(parent) if parent < index => children[parent].push(index),
+                _ => roots.push(index),
+            }
+        }
+
+        fn entry_to_json(
+            entries: &[TimePassesEntry],
+            children: &[Vec<usize>],
+            crate_name: &Option<String>,
+            index: usize,
+        ) -> Json {
+            let entry = &entries[index];
+            let mut obj = BTreeMap::new();
+            obj.insert("label".to_string(), entry.label.to_json());
+            obj.insert("crate".to_string(), crate_name.to_json());
+            obj.insert("start_secs".to_string(), entry.start.as_secs_f64().to_json());
+            obj.insert(
+                "wall_time_secs".to_string(),
+                entry.duration.map(|duration| duration.as_secs_f64()).to_json(),
+            );
+            obj.insert("rss_start".to_string(), entry.start_rss.to_json());
+            obj.insert("rss_end".to_string(), entry.end_rss.to_json());
+            let children_json = children[index]
+                .iter()
+                .map(|&child| entry_to_json(entries, children, crate_name, child))
+                .collect();
+            obj.insert("children".to_string(), Json::Array(children_json));
+            Json::Object(obj)
+        }
+
+        let mut obj = BTreeMap::new();
+        obj.insert("crate".to_string(), crate_name.to_json());
+        obj.insert(
+            "activities".to_string(),
+            Json::Array(
+                roots
+                    .into_iter()
+                    .map(|root| entry_to_json(&entries, &children, &crate_name, root))
+                    .collect(),
+            ),
+        );
+        Json::Object(obj)
+    }
+
+    fn write(&self) -> io::Result<()> {
+        fs::write(&self.path, self.to_json().pretty().to_string())
+    }
+}
+
+thread_local! {
+    /// For each query event of `-Z self-profile-summary` in progress on the
+    /// current thread, innermost last, the time spent in the events nested in it.
+    static OPEN_QUERY_SUMMARY_EVENTS: RefCell<Vec<Duration>> = RefCell::new(Vec::new());
+}
+
+#[derive(Clone, Copy)]
+enum QuerySummaryEvent {
+    Provider,
+    IncrCacheLoad,
+}
+
+/// Times a query event for `-Z self-profile-summary`. The event is recorded
+/// when the guard is dropped, if it was given a query invocation.
+struct QuerySummaryTiming<'a> {
+    summary: &'a QuerySummary,
+    event: QuerySummaryEvent,
+    start: Instant,
+    query_invocation_id: Option<QueryInvocationId>,
+}
+
+impl<'a> QuerySummaryTiming<'a> {
+    fn start(summary: &'a QuerySummary, event: QuerySummaryEvent) -> Self {
+        OPEN_QUERY_SUMMARY_EVENTS
+            .with(|open_events| open_events.borrow_mut().push(Duration::default()));
+        QuerySummaryTiming { summary, event, start: Instant::now(), query_invocation_id: None }
+    }
+}
+
+impl Drop for QuerySummaryTiming<'_> {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        let nested_time = OPEN_QUERY_SUMMARY_EVENTS.with(|open_events| {
+            let mut open_events = open_events.borrow_mut();
+            let nested_time = open_events.pop().unwrap_or_default();
+            if let Some(parent_nested_time) = open_events.last_mut() {
+                *parent_nested_time += duration;
+            }
+            nested_time
+        });
+        if let Some(query_invocation_id) = &self.query_invocation_id {
+            let mut invocations = self.summary.invocations.lock();
+            let stats = invocations.entry(query_invocation_id.0).or_default();
+            match self.event {
+                QuerySummaryEvent::Provider => {
+                    stats.executions += 1;
+                    stats.self_time += duration.checked_sub(nested_time).unwrap_or_default();
+                }
+                QuerySummaryEvent::IncrCacheLoad => {
+                    stats.incr_loads += 1;
+                    stats.incr_load_time += duration;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct QueryStats {
+    /// The time spent executing the query provider, excluding the time spent
+    /// in the queries it invoked.
+    self_time: Duration,
+    executions: usize,
+    cache_hits: usize,
+    incr_load_time: Duration,
+    incr_loads: usize,
+}
+
+impl QueryStats {
+    fn add(&mut self, other: &QueryStats) {
+        self.self_time += other.self_time;
+        self.executions += other.executions;
+        self.cache_hits += other.cache_hits;
+        self.incr_load_time += other.incr_load_time;
+        self.incr_loads += other.incr_loads;
+    }
+}
+
+/// Aggregates the query events of a session by query, for `-Z self-profile-summary`.
+///
+/// Events are recorded by query invocation, as the query names are only
+/// known to the query engine. It maps the invocations to their queries when
+/// the summary is printed.
+#[derive(Default)]
+pub struct QuerySummary {
+    invocations: Mutex<FxHashMap<u32, QueryStats>>,
+}
+
+impl QuerySummary {
+    fn record_cache_hit(&self, query_invocation_id: &QueryInvocationId) {
+        self.invocations.lock().entry(query_invocation_id.0).or_default().cache_hits += 1;
+    }
+
+    /// Prints the `top_n` queries with the highest self time to stderr, with
+    /// `query_name` giving the name of the query of each invocation.
+    pub fn print(
+        &self,
+        top_n: usize,
+        query_name: impl Fn(QueryInvocationId) -> Option<&'static str>,
+    ) {
+        let mut queries: FxHashMap<&'static str, QueryStats> = FxHashMap::default();
+        for (&invocation, stats) in self.invocations.lock().iter() {
+            let name = query_name(QueryInvocationId(invocation)).unwrap_or("<unknown>");
+            queries.entry(name).or_default().add(stats);
+        }
+
+        let total_self_time: Duration = queries.values().map(|stats| stats.self_time).sum();
+        let mut queries: Vec<_> = queries.into_iter().collect();
+        queries.sort_by(|(name1, stats1), (name2, stats2)| {
+            stats2.self_time.cmp(&stats1.self_time).then(name1.cmp(name2))
+        });
+
+        let name_width = queries.iter().take(top_n).map(|(name, _)| name.len()).max().unwrap_or(0);
+        let name_width = name_width.max("query".len());
+        eprintln!(
+            "\nSelf-profile summary: top {} of {} queries by self time (total {}s)",
+            top_n.min(queries.len()),
+            queries.len(),
+            duration_to_secs_str(total_self_time),
+        );
+        eprintln!(
+            "{:<width$}  {:>9}  {:>7}  {:>10}  {:>10}  {:>10}  {:>15}",
+            "query",
+            "self time",
+            "% total",
+            "executions",
+            "cache hits",
+            "incr loads",
+            "incr load time",
+            width = name_width,
+        );
+        for (name, stats) in queries.iter().take(top_n) {
+            let percent = if total_self_time == Duration::default() {
+                0.0
+            } else {
+                stats.self_time.as_secs_f64() * 100.0 / total_self_time.as_secs_f64()
+            };
+            eprintln!(
+                "{:<width$}  {:>9}  {:>6.2}%  {:>10}  {:>10}  {:>10}  {:>15}",
+                name,
+                duration_to_secs_str(stats.self_time),
+                percent,
+                stats.executions,
+                stats.cache_hits,
+                stats.incr_loads,
+                duration_to_secs_str(stats.incr_load_time),
+                width = name_width,
+            );
+        }
+    }
+}
+
 // Memory reporting
 cfg_if! {
     if #[cfg(windows)] {
@@ -16,9 +16,10 @@
 
 use rustc_ast as ast;
 use rustc_codegen_ssa::{traits::CodegenBackend, CodegenResults};
+use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::profiling::{get_resident_set_size, print_time_passes_entry};
 use rustc_data_structures::sync::SeqCst;
-use rustc_errors::registry::{InvalidErrorCode, Registry};
+use rustc_errors::registry::{Explanation, InvalidErrorCode, Registry};
 use rustc_errors::{ErrorReported, PResult};
 use rustc_feature::find_gated_cfg;
 use rustc_hir::def_id::LOCAL_CRATE;
@@ -26,14 +27,16 @@
 use rustc_interface::{interface, Queries};
 use rustc_lint::LintStore;
 use rustc_metadata::locator;
+use rustc_middle::lint::LintLevelSource;
 use rustc_middle::middle::cstore::MetadataLoader;
 use rustc_save_analysis as save;
 use rustc_save_analysis::DumpHandler;
-use rustc_serialize::json::{self, ToJson};
+use rustc_serialize::json::{self, Json, ToJson};
 use rustc_session::config::nightly_options;
-use rustc_session::config::{ErrorOutputType, Input, OutputType, PrintRequest, TrimmedDefPaths};
+use rustc_session::config::TrimmedDefPaths;
+use rustc_session::config::{ErrorOutputType, ExplainFormat, Input, OutputType, PrintRequest};
 use rustc_session::getopts;
-use rustc_session::lint::{Lint, LintId};
+use rustc_session::lint::{FutureIncompatibleInfo, Level, Lint, LintId};
 use rustc_session::{config, DiagnosticOutput, Session};
 use rustc_session::{early_error, early_warn};
 use rustc_span::source_map::{FileLoader, FileName};
@@ -41,6 +44,7 @@
 
 use std::borrow::Cow;
 use std::cmp::max;
+use std::collections::BTreeMap;
 use std::default::Default;
 use std::env;
 use std::ffi::OsString;
@@ -225,7 +229,7 @@ fn run_compiler(
     };
 
     if let Some(ref code) = matches.opt_str("explain") {
-        handle_explain(diagnostics_registry(), code, sopts.error_format);
+        handle_explain(diagnostics_registry(), code, sopts.error_format, sopts.explain_format);
         return Ok(());
     }
 
@@ -254,6 +258,7 @@ fn run_compiler(
                     }
                     let should_stop = RustcDefaultCalls::print_crate_info(
                         &***compiler.codegen_backend(),
+                        compiler.register_lints(),
                         compiler.session(),
                         None,
                         &odir,
@@ -310,6 +315,7 @@ fn run_compiler(
         let sess = compiler.session();
         let should_stop = RustcDefaultCalls::print_crate_info(
             &***compiler.codegen_backend(),
+            compiler.register_lints(),
             sess,
             Some(compiler.input()),
             compiler.output_dir(),
@@ -545,10 +551,20 @@ fn stderr_isatty() -> bool {
     atty::is(atty::Stream::Stderr)
 }
 
-fn handle_explain(registry: Registry, code: &str, output: ErrorOutputType) {
+fn handle_explain(
+    registry: Registry,
+    code: &str,
+    output: ErrorOutputType,
+    explain_format: ExplainFormat,
+) {
     let normalised =
         if code.starts_with('E') { code.to_string() } else { format!("E{0:0>4}", code) };
     match registry.try_find_description(&normalised) {
+        // Codes without an explanation are listed by `--print error-codes` too,
+        // so tools get an object with a `null` explanation for them as well.
+        Ok(description) if explain_format == ExplainFormat::Json => {
+            println!("{}", explanation_to_json(&normalised, description).pretty());
+        }
         Ok(Some(description)) => {
             let mut is_in_code_block = false;
             let mut text = String::new();
@@ -582,6 +598,56 @@ fn handle_explain(registry: Registry, code: &str, output: ErrorOutputType) {
     }
 }
 
+/// The parts of the explanation of an error code, as printed by
+/// `--explain-format=json`.
+fn explanation_to_json(code: &str, description: Option<&'static str>) -> Json {
+    let explanation = description.map(Explanation::parse);
+    let examples: Vec<Json> = explanation
+        .iter()
+        .flat_map(|explanation| &explanation.examples)
+        .map(|example| {
+            let kind = if example.is_erroneous { "erroneous" } else { "fixed" };
+            let mut obj = BTreeMap::new();
+            obj.insert("kind", kind.to_json());
+            obj.insert("attributes", example.attributes.to_json());
+            obj.insert("code", example.code.to_json());
+            obj.to_json()
+        })
+        .collect();
+
+    let mut obj = BTreeMap::new();
+    obj.insert("code", code.to_json());
+    obj.insert("title", explanation.as_ref().map(|e| e.title.clone()).to_json());
+    obj.insert("markdown", description.map(str::to_string).to_json());
+    obj.insert("examples", examples.to_json());
+    obj.insert("is_emitted", explanation.as_ref().map_or(true, |e| e.is_emitted).to_json());
+    obj.to_json()
+}
+
+/// Lists all error codes with the summary of their explanation, for
+/// `--print error-codes`.
+fn print_error_codes(registry: &Registry, explain_format: ExplainFormat) {
+    let descriptions = registry.descriptions();
+    if explain_format == ExplainFormat::Json {
+        let codes: Vec<Json> = descriptions
+            .into_iter()
+            .map(|(code, description)| explanation_to_json(code, description))
+            .collect();
+        println!("{}", codes.to_json().pretty());
+        return;
+    }
+
+    for (code, description) in descriptions {
+        match description.map(Explanation::parse) {
+            Some(explanation) if explanation.is_emitted => {
+                println!("{}: {}", code, explanation.title)
+            }
+            Some(explanation) => println!("{}: {} (no longer emitted)", code, explanation.title),
+            None => println!("{}: (no extended information)", code),
+        }
+    }
+}
+
 fn show_content_with_pager(content: &str) {
     let pager_name = env::var_os("PAGER").unwrap_or_else(|| {
         if cfg!(windows) { OsString::from("more.com") } else { OsString::from("less") }
@@ -670,6 +736,7 @@ pub fn list_metadata(
 
     fn print_crate_info(
         codegen_backend: &dyn CodegenBackend,
+        register_lints: &Option<Box<dyn Fn(&Session, &mut LintStore) + Send + Sync>>,
         sess: &Session,
         input: Option<&Input>,
         odir: &Option<PathBuf>,
@@ -766,6 +833,19 @@ fn print_crate_info(
                 RelocationModels | CodeModels | TlsModels | TargetCPUs | TargetFeatures => {
                     codegen_backend.print(*req, sess);
                 }
+                ErrorCodes => print_error_codes(&diagnostics_registry(), sess.opts.explain_format),
+                LintLevels | LintLevelsJson => {
+                    // Lints of plugins are only known once the crate is parsed,
+                    // so only builtin and driver-registered lints are listed.
+                    let mut lint_store = rustc_lint::new_lint_store(
+                        sess.opts.debugging_opts.no_interleave_lints,
+                        sess.unstable_options(),
+                    );
+                    if let Some(register_lints) = register_lints {
+                        register_lints(sess, &mut lint_store);
+                    }
+                    print_lint_levels(sess, &lint_store, *req == LintLevelsJson);
+                }
                 // Any output here interferes with Cargo's parsing of other printed output
                 PrintRequest::NativeStaticLibs => {}
             }
@@ -955,6 +1035,105 @@ fn sort_lint_groups(
     }
 }
 
+/// Prints the level of every lint after applying the command line, for
+/// `--print lint-levels` and `--print lint-levels-json`.
+fn print_lint_levels(sess: &Session, lint_store: &LintStore, json: bool) {
+    let mut groups: FxHashMap<LintId, Vec<&'static str>> = FxHashMap::default();
+    for (group, lint_ids, _) in lint_store.get_lint_groups() {
+        for id in lint_ids {
+            groups.entry(id).or_default().push(group);
+        }
+    }
+    for lint_groups in groups.values_mut() {
+        lint_groups.sort_unstable();
+    }
+
+    let mut levels = rustc_lint::command_line_lint_levels(sess, lint_store);
+    levels.sort_by_key(|(lint, _)| lint.name);
+
+    // The flag which set the level, e.g. `-Dwarnings`.
+    let source = |src: LintLevelSource| match src {
+        LintLevelSource::CommandLine(name, level) => {
+            let flag = match level {
+                Level::Allow => "-A",
+                Level::Warn => "-W",
+                Level::Deny => "-D",
+                Level::Forbid => "-F",
+                Level::Expect => unreachable!(),
+            };
+            Some(format!("{}{}", flag, name))
+        }
+        LintLevelSource::Default | LintLevelSource::Node(..) => None,
+    };
+
+    if json {
+        let lints: Vec<Json> = levels
+            .into_iter()
+            .map(|(lint, (level, src))| {
+                let future_incompatible = lint.future_incompatible.map(|info| {
+                    let mut obj = BTreeMap::new();
+                    obj.insert("reference", info.reference.to_json());
+                    obj.insert("edition", info.edition.map(|e| e.to_string()).to_json());
+                    obj.insert("future_breakage", info.future_breakage.is_some().to_json());
+                    obj.to_json()
+                });
+                let lint_groups = groups.get(&LintId::of(lint)).map_or(&[][..], |g| &g[..]);
+
+                let mut obj = BTreeMap::new();
+                obj.insert("name", lint.name_lower().to_json());
+                obj.insert("default_level", lint.default_level(sess.edition()).as_str().to_json());
+                obj.insert("level", level.as_str().to_json());
+                obj.insert("source", source(src).to_json());
+                obj.insert(
+                    "groups",
+                    Json::Array(lint_groups.iter().map(|group| group.to_json()).collect()),
+                );
+                obj.insert("future_incompatible", future_incompatible.to_json());
+                obj.insert("is_plugin", lint.is_plugin.to_json());
+                obj.to_json()
+            })
+            .collect();
+        println!("{}", lints.to_json().pretty());
+        return;
+    }
+
+    let header = ["name", "default", "level", "source", "groups", "future-incompat"];
+    let mut rows: Vec<Vec<String>> = vec![header.iter().map(|s| s.to_string()).collect()];
+    for (lint, (level, src)) in levels {
+        let lint_groups = match groups.get(&LintId::of(lint)) {
+            Some(lint_groups) => lint_groups.join(","),
+            None => "-".to_string(),
+        };
+        let future_incompatible = match lint.future_incompatible {
+            None => "no".to_string(),
+            Some(FutureIncompatibleInfo { edition: Some(edition), .. }) => {
+                format!("edition-{}", edition)
+            }
+            Some(FutureIncompatibleInfo { future_breakage: Some(_), .. }) => {
+                "reported-in-deps".to_string()
+            }
+            Some(_) => "yes".to_string(),
+        };
+        rows.push(vec![
+            lint.name_lower().replace("_", "-"),
+            lint.default_level(sess.edition()).as_str().to_string(),
+            level.as_str().to_string(),
+            source(src).unwrap_or_else(|| "default".to_string()),
+            lint_groups.replace("_", "-"),
+            future_incompatible,
+        ]);
+    }
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in rows {
+        let cells: Vec<String> =
+            row.iter().zip(&widths).map(|(cell, &width)| format!("{:1$}", cell, width)).collect();
+        println!("{}", cells.join("  ").trim_end());
+    }
+}
+
 fn describe_debug_flags() {
     println!("\nAvailable options:\n");
     print_flag_list("-Z", config::DB_OPTIONS);
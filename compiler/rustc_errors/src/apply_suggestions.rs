@@ -0,0 +1,269 @@
+//! Applying machine-applicable suggestions to source files (`-Z apply-suggestions`).
+//!
+//! When requested, the `Handler` keeps every `MachineApplicable` suggestion of
+//! the diagnostics it emits. At the end of the session, these suggestions are
+//! resolved to byte ranges of the source files as they are on disk, the ones
+//! that conflict with an earlier suggestion are dropped, and the remaining
+//! edits are either written back to the files or rendered as a unified diff.
+
+use crate::CodeSuggestion;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, RealFileName, SourceFileHash};
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+/// The number of unchanged lines shown around each change of a unified diff.
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// A replacement of the bytes `lo..hi` of a file, as it is on disk, by `replacement`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub lo: usize,
+    pub hi: usize,
+    pub replacement: String,
+}
+
+impl Edit {
+    fn overlaps(&self, other: &Edit) -> bool {
+        if self.lo == self.hi && other.lo == other.hi {
+            // The order of two insertions at the same position is ambiguous.
+            return self.lo == other.lo;
+        }
+        self.lo < other.hi && other.lo < self.hi
+    }
+}
+
+/// The suggestions applying to a single source file.
+pub struct FileEdits {
+    pub path: PathBuf,
+    /// The hash of the file contents the edits were computed for.
+    pub src_hash: SourceFileHash,
+    /// The edits of each suggestion. A suggestion is applied either entirely or not at all.
+    pub suggestions: Vec<Vec<Edit>>,
+}
+
+/// Resolves the spans of `suggestions` to edits of the local source files.
+///
+/// Suggestions with several substitutions are skipped: these are alternatives
+/// to each other, and picking one is up to the user. Suggestions touching macro
+/// expansions, several files, or files that aren't part of the local crate are
+/// skipped too, since applying them could change more than the suggestion
+/// intends.
+pub fn collect_file_edits(sm: &SourceMap, suggestions: &[CodeSuggestion]) -> Vec<FileEdits> {
+    let mut files: FxIndexMap<PathBuf, FileEdits> = FxIndexMap::default();
+
+    'suggestions: for suggestion in suggestions {
+        let substitution = match &suggestion.substitutions[..] {
+            [substitution] => substitution,
+            _ => continue,
+        };
+
+        let mut file = None;
+        let mut edits = Vec::with_capacity(substitution.parts.len());
+        for part in &substitution.parts {
+            if part.span.is_dummy() || part.span.from_expansion() {
+                continue 'suggestions;
+            }
+            let sf = sm.lookup_source_file(part.span.lo());
+            if sf.is_imported() || sf.start_pos > part.span.hi() || sf.end_pos < part.span.hi() {
+                continue 'suggestions;
+            }
+            let path = match sf.unmapped_path.as_ref().unwrap_or(&sf.name) {
+                FileName::Real(RealFileName::Named(path)) => path.clone(),
+                _ => continue 'suggestions,
+            };
+            match &file {
+                None => file = Some((path, sf.src_hash)),
+                Some((file_path, _)) if *file_path == path => {}
+                Some(_) => continue 'suggestions,
+            }
+            edits.push(Edit {
+                lo: sf.original_relative_byte_pos(part.span.lo()).0 as usize,
+                hi: sf.original_relative_byte_pos(part.span.hi()).0 as usize,
+                replacement: part.snippet.clone(),
+            });
+        }
+
+        if let Some((path, src_hash)) = file {
+            files
+                .entry(path.clone())
+                .or_insert_with(|| FileEdits { path, src_hash, suggestions: vec![] })
+                .suggestions
+                .push(edits);
+        }
+    }
+
+    files.into_iter().map(|(_, file)| file).collect()
+}
+
+/// Selects the suggestions to apply to a file, in order of their position.
+///
+/// A suggestion is dropped if its edits overlap each other or the edits of a
+/// suggestion selected before it; suggestions that are identical to a selected
+/// one are ignored. Returns the selected edits, sorted by position, and the
+/// number of dropped suggestions.
+pub fn resolve_overlaps(mut suggestions: Vec<Vec<Edit>>) -> (Vec<Edit>, usize) {
+    for edits in &mut suggestions {
+        edits.sort_by_key(|edit| (edit.lo, edit.hi));
+    }
+    suggestions.sort_by_key(|edits| edits.first().map(|edit| (edit.lo, edit.hi)));
+
+    let mut selected: Vec<Vec<Edit>> = vec![];
+    let mut dropped = 0;
+    for edits in suggestions {
+        if edits.is_empty() || selected.contains(&edits) {
+            continue;
+        }
+        let is_self_overlapping = edits.windows(2).any(|pair| pair[0].overlaps(&pair[1]));
+        let is_conflicting = selected
+            .iter()
+            .flatten()
+            .any(|selected_edit| edits.iter().any(|edit| edit.overlaps(selected_edit)));
+        if is_self_overlapping || is_conflicting {
+            dropped += 1;
+        } else {
+            selected.push(edits);
+        }
+    }
+
+    let mut edits: Vec<Edit> = selected.into_iter().flatten().collect();
+    edits.sort_by_key(|edit| (edit.lo, edit.hi));
+    (edits, dropped)
+}
+
+/// Applies `edits`, sorted by position and not overlapping, to `src`.
+pub fn apply_edits(src: &str, edits: &[Edit]) -> String {
+    let mut result = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits {
+        result.push_str(&src[pos..edit.lo]);
+        result.push_str(&edit.replacement);
+        pos = edit.hi;
+    }
+    result.push_str(&src[pos..]);
+    result
+}
+
+/// Renders the result of applying `edits`, sorted by position and not
+/// overlapping, to the contents `src` of `path` as a unified diff.
+pub fn unified_diff(path: &Path, src: &str, edits: &[Edit]) -> String {
+    if edits.is_empty() {
+        return String::new();
+    }
+
+    // The byte range of each line, including its line terminator.
+    let mut lines: Vec<Range<usize>> = vec![];
+    let mut line_start = 0;
+    for (i, _) in src.match_indices('\n') {
+        lines.push(line_start..i + 1);
+        line_start = i + 1;
+    }
+    if line_start < src.len() || lines.is_empty() {
+        lines.push(line_start..src.len());
+    }
+    let line_of =
+        |pos: usize| lines.iter().position(|line| pos < line.end).unwrap_or(lines.len() - 1);
+
+    // Group the edits into changes of consecutive whole lines, given by the
+    // first and the last changed line. An edit removing a line terminator
+    // joins the following line, so that line is changed too.
+    let mut changes: Vec<(Range<usize>, Vec<&Edit>)> = vec![];
+    for edit in edits {
+        let first = line_of(edit.lo);
+        let last = if edit.hi > edit.lo { line_of(edit.hi - 1) } else { first };
+        let last = if edit.hi > edit.lo && src.as_bytes()[edit.hi - 1] == b'\n' {
+            (last + 1).min(lines.len() - 1)
+        } else {
+            last
+        };
+        match changes.last_mut() {
+            Some((range, change_edits)) if first <= range.end + 1 => {
+                range.end = range.end.max(last);
+                change_edits.push(edit);
+            }
+            _ => changes.push((first..last, vec![edit])),
+        }
+    }
+
+    let mut diff = String::new();
+    writeln!(diff, "--- a/{}", path.display()).unwrap();
+    writeln!(diff, "+++ b/{}", path.display()).unwrap();
+
+    // Group the changes into hunks, which share their context lines.
+    let mut hunks: Vec<Vec<(Range<usize>, Vec<&Edit>)>> = vec![];
+    for change in changes {
+        match hunks.last_mut() {
+            Some(hunk)
+                if change.0.start <= hunk.last().unwrap().0.end + 2 * DIFF_CONTEXT_LINES + 1 =>
+            {
+                hunk.push(change)
+            }
+            _ => hunks.push(vec![change]),
+        }
+    }
+
+    // The difference between the line numbers of the new and the old file.
+    let mut line_delta: isize = 0;
+    for hunk in hunks {
+        let first_line = hunk[0].0.start.saturating_sub(DIFF_CONTEXT_LINES);
+        let last_line = (hunk.last().unwrap().0.end + DIFF_CONTEXT_LINES).min(lines.len() - 1);
+
+        let mut body = String::new();
+        let mut old_len = 0;
+        let mut new_len = 0;
+        let mut line = first_line;
+        for (range, change_edits) in &hunk {
+            for context in line..range.start {
+                push_diff_line(&mut body, ' ', &src[lines[context].clone()]);
+            }
+            let old_start = lines[range.start].start;
+            let old_text = &src[old_start..lines[range.end].end];
+            let relative_edits: Vec<Edit> = change_edits
+                .iter()
+                .map(|edit| Edit {
+                    lo: edit.lo - old_start,
+                    hi: edit.hi - old_start,
+                    replacement: edit.replacement.clone(),
+                })
+                .collect();
+            let new_text = apply_edits(old_text, &relative_edits);
+            for old_line in old_text.split_inclusive('\n') {
+                push_diff_line(&mut body, '-', old_line);
+            }
+            for new_line in new_text.split_inclusive('\n') {
+                push_diff_line(&mut body, '+', new_line);
+            }
+            old_len += range.end + 1 - range.start;
+            new_len += new_text.split_inclusive('\n').count();
+            line = range.end + 1;
+        }
+        for context in line..=last_line {
+            push_diff_line(&mut body, ' ', &src[lines[context].clone()]);
+        }
+        let context_len = last_line + 1 - first_line - old_len;
+        let old_len = old_len + context_len;
+        let new_len = new_len + context_len;
+
+        let old_start = first_line as isize + 1;
+        writeln!(diff, "@@ -{},{} +{},{} @@", old_start, old_len, old_start + line_delta, new_len)
+            .unwrap();
+        diff.push_str(&body);
+        line_delta += new_len as isize - old_len as isize;
+    }
+
+    diff
+}
+
+fn push_diff_line(diff: &mut String, prefix: char, line: &str) {
+    diff.push(prefix);
+    diff.push_str(line);
+    if !line.ends_with('\n') {
+        diff.push_str("\n\\ No newline at end of file\n");
+    }
+}
@@ -0,0 +1,117 @@
+use super::*;
+
+fn edit(lo: usize, hi: usize, replacement: &str) -> Edit {
+    Edit { lo, hi, replacement: replacement.to_owned() }
+}
+
+#[test]
+fn apply_in_order() {
+    let src = "let x = 1;\nlet y = 2;\n";
+    let edits = [edit(4, 5, "_x"), edit(11, 14, "let mut"), edit(21, 21, "  // two")];
+    assert_eq!(apply_edits(src, &edits), "let _x = 1;\nlet mut y = 2;  // two\n");
+}
+
+#[test]
+fn overlapping_suggestions_are_dropped() {
+    let (edits, dropped) = resolve_overlaps(vec![
+        vec![edit(10, 20, "b")],
+        vec![edit(0, 5, "a")],
+        vec![edit(15, 25, "c")],
+        vec![edit(0, 5, "a")],
+        vec![edit(20, 20, "d")],
+    ]);
+    assert_eq!(edits, [edit(0, 5, "a"), edit(10, 20, "b"), edit(20, 20, "d")]);
+    assert_eq!(dropped, 1);
+}
+
+#[test]
+fn suggestions_are_applied_entirely_or_not_at_all() {
+    let (edits, dropped) = resolve_overlaps(vec![
+        vec![edit(0, 0, "("), edit(3, 3, ")")],
+        vec![edit(3, 3, "?"), edit(10, 12, "x")],
+        vec![edit(5, 8, "y"), edit(6, 7, "z")],
+    ]);
+    assert_eq!(edits, [edit(0, 0, "("), edit(3, 3, ")")]);
+    assert_eq!(dropped, 2);
+}
+
+#[test]
+fn diff_with_context() {
+    let src = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n16\n";
+    // Replace "2", and insert a line after "14".
+    let edits = [edit(2, 3, "two"), edit(33, 33, "14.5\n")];
+    assert_eq!(
+        unified_diff(Path::new("src/lib.rs"), src, &edits),
+        "--- a/src/lib.rs\n\
+         +++ b/src/lib.rs\n\
+         @@ -1,5 +1,5 @@\n \
+         1\n\
+         -2\n\
+         +two\n \
+         3\n \
+         4\n \
+         5\n\
+         @@ -12,5 +12,6 @@\n \
+         12\n \
+         13\n \
+         14\n\
+         -15\n\
+         +14.5\n\
+         +15\n \
+         16\n",
+    );
+}
+
+#[test]
+fn diff_of_close_changes_shares_a_hunk() {
+    let src = "a\nb\nc\nd\ne\nf\ng\nh\n";
+    let edits = [edit(0, 1, "A"), edit(14, 15, "H")];
+    assert_eq!(
+        unified_diff(Path::new("x.rs"), src, &edits),
+        "--- a/x.rs\n\
+         +++ b/x.rs\n\
+         @@ -1,8 +1,8 @@\n\
+         -a\n\
+         +A\n \
+         b\n \
+         c\n \
+         d\n \
+         e\n \
+         f\n \
+         g\n\
+         -h\n\
+         +H\n",
+    );
+}
+
+#[test]
+fn diff_joining_lines() {
+    let src = "fn f() {\n}\n";
+    let edits = [edit(8, 9, "")];
+    assert_eq!(
+        unified_diff(Path::new("x.rs"), src, &edits),
+        "--- a/x.rs\n\
+         +++ b/x.rs\n\
+         @@ -1,2 +1,1 @@\n\
+         -fn f() {\n\
+         -}\n\
+         +fn f() {}\n",
+    );
+}
+
+#[test]
+fn diff_without_trailing_newline() {
+    let src = "a\nb";
+    let edits = [edit(2, 3, "c")];
+    assert_eq!(
+        unified_diff(Path::new("x.rs"), src, &edits),
+        "--- a/x.rs\n\
+         +++ b/x.rs\n\
+         @@ -1,2 +1,2 @@\n \
+         a\n\
+         -b\n\
+         \\ No newline at end of file\n\
+         +c\n\
+         \\ No newline at end of file\n",
+    );
+}
@@ -193,6 +193,11 @@ pub trait Emitter {
     /// other formats can, and will, simply ignore it.
     fn emit_artifact_notification(&mut self, _path: &Path, _artifact_type: &str) {}
 
+    /// Emit a record of an `--extern` crate that isn't used by the crate.
+    /// This is currently only supported for the JSON format,
+    /// other formats can, and will, simply ignore it.
+    fn emit_unused_extern(&mut self, _name: &str, _paths: &[&str]) {}
+
     fn emit_future_breakage_report(&mut self, _diags: Vec<(FutureBreakage, Diagnostic)>) {}
 
     /// Checks if should show explanations about "rustc --explain"
@@ -200,6 +205,12 @@ fn should_show_explain(&self) -> bool {
         true
     }
 
+    /// Checks if should show the summary of the emitted errors and warnings,
+    /// like "aborting due to previous error", at the end of the session
+    fn should_show_error_count(&self) -> bool {
+        true
+    }
+
     /// Checks if we can use colors in the current output stream.
     fn supports_color(&self) -> bool {
         false
@@ -134,6 +134,19 @@ fn emit_artifact_notification(&mut self, path: &Path, artifact_type: &str) {
         }
     }
 
+    fn emit_unused_extern(&mut self, name: &str, paths: &[&str]) {
+        let data = UnusedExtern { unused_extern: name, paths: paths.to_vec() };
+        let result = if self.pretty {
+            writeln!(&mut self.dst, "{}", as_pretty_json(&data))
+        } else {
+            writeln!(&mut self.dst, "{}", as_json(&data))
+        }
+        .and_then(|_| self.dst.flush());
+        if let Err(e) = result {
+            panic!("failed to print unused extern: {:?}", e);
+        }
+    }
+
     fn emit_future_breakage_report(&mut self, diags: Vec<(FutureBreakage, crate::Diagnostic)>) {
         let data: Vec<FutureBreakageItem> = diags
             .into_iter()
@@ -311,6 +324,14 @@ struct ArtifactNotification<'a> {
     emit: &'a str,
 }
 
+#[derive(Encodable)]
+struct UnusedExtern<'a> {
+    /// The name of the `--extern` crate that isn't used.
+    unused_extern: &'a str,
+    /// The paths given for the crate by its `--extern` options.
+    paths: Vec<&'a str>,
+}
+
 #[derive(Encodable)]
 struct FutureBreakageItem {
     future_breakage_date: Option<&'static str>,
@@ -6,6 +6,7 @@
 #![feature(crate_visibility_modifier)]
 #![feature(backtrace)]
 #![feature(nll)]
+#![feature(once_cell)]
 
 #[macro_use]
 extern crate rustc_macros;
@@ -17,7 +18,7 @@
 
 use emitter::{is_case_difference, Emitter, EmitterWriter};
 use registry::Registry;
-use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
 use rustc_data_structures::stable_hasher::StableHasher;
 use rustc_data_structures::sync::{self, Lock, Lrc};
 use rustc_data_structures::AtomicRef;
@@ -38,12 +39,14 @@
 use termcolor::{Color, ColorSpec};
 
 pub mod annotate_snippet_emitter_writer;
+pub mod apply_suggestions;
 mod diagnostic;
 mod diagnostic_builder;
 pub mod emitter;
 pub mod json;
 mod lock;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 pub use snippet::Style;
@@ -340,6 +343,28 @@ struct HandlerInner {
     deduplicated_warn_count: usize,
 
     future_breakage_diagnostics: Vec<Diagnostic>,
+
+    /// The machine-applicable suggestions of all emitted diagnostics, if
+    /// `flags.collect_machine_applicable_suggestions` is set.
+    machine_applicable_suggestions: Vec<CodeSuggestion>,
+
+    /// The number of emitted errors and warnings for each file and for each
+    /// error code or lint, if `flags.error_limit_per_file` or
+    /// `flags.error_limit_per_code` is set.
+    emitted_per_file: FxHashMap<String, usize>,
+    emitted_per_code: FxHashMap<String, usize>,
+    /// The number of errors and warnings which were not emitted because of
+    /// these limits, in total and for each file and code which reached its limit.
+    suppressed_count: usize,
+    suppressed_per_file: FxIndexMap<String, usize>,
+    suppressed_per_code: FxIndexMap<String, usize>,
+
+    /// The spans of the `#[expect]` lint attributes which were fulfilled by a
+    /// diagnostic at the `Expect` level. Like other diagnostics, these are
+    /// recorded as side effects of the dep-graph task that emitted them, so
+    /// that they are fulfilled again when incremental compilation reuses the
+    /// task's result.
+    fulfilled_expectations: FxHashSet<Span>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -372,6 +397,15 @@ pub struct HandlerFlags {
     pub macro_backtrace: bool,
     /// If true, identical diagnostics are reported only once.
     pub deduplicate_diagnostics: bool,
+    /// If true, the machine-applicable suggestions of emitted diagnostics are kept.
+    /// (rustc: see `-Z apply-suggestions`)
+    pub collect_machine_applicable_suggestions: bool,
+    /// The maximum number of errors and warnings to emit for each file.
+    /// (rustc: see `-Z error-limit-per-file`)
+    pub error_limit_per_file: Option<usize>,
+    /// The maximum number of errors and warnings to emit for each error code or lint.
+    /// (rustc: see `-Z error-limit-per-code`)
+    pub error_limit_per_code: Option<usize>,
 }
 
 impl Drop for HandlerInner {
@@ -454,6 +488,13 @@ pub fn with_emitter_and_flags(
                 emitted_diagnostics: Default::default(),
                 stashed_diagnostics: Default::default(),
                 future_breakage_diagnostics: Vec::new(),
+                machine_applicable_suggestions: Vec::new(),
+                emitted_per_file: Default::default(),
+                emitted_per_code: Default::default(),
+                suppressed_count: 0,
+                suppressed_per_file: Default::default(),
+                suppressed_per_code: Default::default(),
+                fulfilled_expectations: Default::default(),
             }),
         }
     }
@@ -733,6 +774,16 @@ pub fn take_future_breakage_diagnostics(&self) -> Vec<Diagnostic> {
         std::mem::take(&mut self.inner.borrow_mut().future_breakage_diagnostics)
     }
 
+    pub fn take_machine_applicable_suggestions(&self) -> Vec<CodeSuggestion> {
+        std::mem::take(&mut self.inner.borrow_mut().machine_applicable_suggestions)
+    }
+
+    /// Returns the spans of all `#[expect]` attributes that have been fulfilled
+    /// so far, see `Level::Expect`.
+    pub fn fulfilled_expectations(&self) -> FxHashSet<Span> {
+        self.inner.borrow().fulfilled_expectations.clone()
+    }
+
     pub fn abort_if_errors(&self) {
         self.inner.borrow_mut().abort_if_errors()
     }
@@ -763,6 +814,10 @@ pub fn emit_artifact_notification(&self, path: &Path, artifact_type: &str) {
         self.inner.borrow_mut().emit_artifact_notification(path, artifact_type)
     }
 
+    pub fn emit_unused_extern(&self, name: &str, paths: &[&str]) {
+        self.inner.borrow_mut().emitter.emit_unused_extern(name, paths)
+    }
+
     pub fn emit_future_breakage_report(&self, diags: Vec<(FutureBreakage, Diagnostic)>) {
         self.inner.borrow_mut().emitter.emit_future_breakage_report(diags)
     }
@@ -805,6 +860,11 @@ fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
 
         (*TRACK_DIAGNOSTICS)(diagnostic);
 
+        if diagnostic.level == Expect {
+            self.fulfilled_expectations.extend(diagnostic.span.primary_span());
+            return;
+        }
+
         if diagnostic.level == Allow {
             return;
         }
@@ -823,11 +883,24 @@ fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
         // Only emit the diagnostic if we've been asked to deduplicate and
         // haven't already emitted an equivalent diagnostic.
         if !(self.flags.deduplicate_diagnostics && already_emitted(self)) {
-            self.emitter.emit_diagnostic(diagnostic);
-            if diagnostic.is_error() {
-                self.deduplicated_err_count += 1;
-            } else if diagnostic.level == Warning {
-                self.deduplicated_warn_count += 1;
+            // Diagnostics suppressed by the error limits are only counted in
+            // the summary printed by `print_suppressed_count`.
+            if self.is_within_error_limits(diagnostic) {
+                self.emitter.emit_diagnostic(diagnostic);
+                if diagnostic.is_error() {
+                    self.deduplicated_err_count += 1;
+                } else if diagnostic.level == Warning {
+                    self.deduplicated_warn_count += 1;
+                }
+            }
+            if self.flags.collect_machine_applicable_suggestions {
+                self.machine_applicable_suggestions.extend(
+                    diagnostic
+                        .suggestions
+                        .iter()
+                        .filter(|sugg| sugg.applicability == Applicability::MachineApplicable)
+                        .cloned(),
+                );
             }
         }
         if diagnostic.is_error() {
@@ -837,6 +910,79 @@ fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
         }
     }
 
+    /// Checks `diagnostic` against `flags.error_limit_per_file` and
+    /// `flags.error_limit_per_code`, and counts it towards them. If either
+    /// limit has already been reached, the diagnostic is recorded as suppressed
+    /// instead and `false` is returned.
+    fn is_within_error_limits(&mut self, diagnostic: &Diagnostic) -> bool {
+        // Only errors and warnings are limited, not fatal errors or bugs.
+        if diagnostic.level != Error && diagnostic.level != Warning {
+            return true;
+        }
+
+        let file = match (self.flags.error_limit_per_file, self.emitter.source_map()) {
+            (Some(_), Some(sm)) => diagnostic
+                .span
+                .primary_span()
+                .filter(|sp| !sp.is_dummy())
+                .map(|sp| sm.span_to_filename(sp).to_string()),
+            _ => None,
+        };
+        let code = match (self.flags.error_limit_per_code, &diagnostic.code) {
+            (Some(_), Some(DiagnosticId::Error(code))) => Some(code.clone()),
+            (Some(_), Some(DiagnosticId::Lint { name, .. })) => Some(name.clone()),
+            _ => None,
+        };
+
+        let file_limit_reached = match (&file, self.flags.error_limit_per_file) {
+            (Some(file), Some(limit)) => self.emitted_per_file.get(file).map_or(0, |&n| n) >= limit,
+            _ => false,
+        };
+        let code_limit_reached = match (&code, self.flags.error_limit_per_code) {
+            (Some(code), Some(limit)) => self.emitted_per_code.get(code).map_or(0, |&n| n) >= limit,
+            _ => false,
+        };
+
+        if file_limit_reached || code_limit_reached {
+            self.suppressed_count += 1;
+            if let (true, Some(file)) = (file_limit_reached, file) {
+                *self.suppressed_per_file.entry(file).or_default() += 1;
+            }
+            if let (true, Some(code)) = (code_limit_reached, code) {
+                *self.suppressed_per_code.entry(code).or_default() += 1;
+            }
+            return false;
+        }
+
+        if let Some(file) = file {
+            *self.emitted_per_file.entry(file).or_default() += 1;
+        }
+        if let Some(code) = code {
+            *self.emitted_per_code.entry(code).or_default() += 1;
+        }
+        true
+    }
+
+    /// Summarizes the diagnostics suppressed by `flags.error_limit_per_file`
+    /// and `flags.error_limit_per_code`, per file and per code.
+    fn print_suppressed_count(&mut self) {
+        let msg = match self.suppressed_count {
+            0 => return,
+            1 => "1 error or warning was not shown because of the error limits".to_string(),
+            count => {
+                format!("{} errors and warnings were not shown because of the error limits", count)
+            }
+        };
+        let mut diag = Diagnostic::new(Level::Note, &msg);
+        for (file, count) in &self.suppressed_per_file {
+            diag.note(&format!("{} not shown for `{}` (`-Z error-limit-per-file`)", count, file));
+        }
+        for (code, count) in &self.suppressed_per_code {
+            diag.note(&format!("{} not shown for `{}` (`-Z error-limit-per-code`)", count, code));
+        }
+        self.emitter.emit_diagnostic(&diag);
+    }
+
     fn emit_artifact_notification(&mut self, path: &Path, artifact_type: &str) {
         self.emitter.emit_artifact_notification(path, artifact_type);
     }
@@ -858,10 +1004,12 @@ fn print_error_count(&mut self, registry: &Registry) {
             1 => "aborting due to previous error".to_string(),
             count => format!("aborting due to {} previous errors", count),
         };
-        if self.treat_err_as_bug() {
+        if self.treat_err_as_bug() || !self.emitter.should_show_error_count() {
             return;
         }
 
+        self.print_suppressed_count();
+
         match (errors.len(), warnings.len()) {
             (0, 0) => return,
             (0, _) => self.emit_diagnostic(&Diagnostic::new(Level::Warning, &warnings)),
@@ -1047,6 +1195,9 @@ pub enum Level {
     Cancelled,
     FailureNote,
     Allow,
+    /// Marks the `#[expect]` lint attribute at the diagnostic's primary span as
+    /// fulfilled. Such a diagnostic is never printed.
+    Expect,
 }
 
 impl fmt::Display for Level {
@@ -1072,7 +1223,7 @@ fn color(self) -> ColorSpec {
                 spec.set_fg(Some(Color::Cyan)).set_intense(true);
             }
             FailureNote => {}
-            Allow | Cancelled => unreachable!(),
+            Allow | Expect | Cancelled => unreachable!(),
         }
         spec
     }
@@ -1087,6 +1238,7 @@ pub fn to_str(self) -> &'static str {
             FailureNote => "failure-note",
             Cancelled => panic!("Shouldn't call on cancelled error"),
             Allow => panic!("Shouldn't call on allowed error"),
+            Expect => panic!("Shouldn't call on lint expectation"),
         }
     }
 
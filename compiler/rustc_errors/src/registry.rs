@@ -1,5 +1,8 @@
 use rustc_data_structures::fx::FxHashMap;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Debug)]
 pub struct InvalidErrorCode;
 
@@ -26,4 +29,121 @@ pub fn try_find_description(
     ) -> Result<Option<&'static str>, InvalidErrorCode> {
         self.long_descriptions.get(code).copied().ok_or(InvalidErrorCode)
     }
+
+    /// Returns all error codes and their long descriptions, sorted by code.
+    pub fn descriptions(&self) -> Vec<(&'static str, Option<&'static str>)> {
+        let mut descriptions: Vec<_> =
+            self.long_descriptions.iter().map(|(&code, &description)| (code, description)).collect();
+        descriptions.sort_by_key(|&(code, _)| code);
+        descriptions
+    }
+}
+
+/// The long-form explanation of an error code, split into the parts that
+/// tools showing it outside of a terminal care about.
+#[derive(Debug)]
+pub struct Explanation {
+    /// The first sentence of the explanation, which summarizes the error.
+    pub title: String,
+    /// The whole explanation, in Markdown.
+    pub markdown: &'static str,
+    /// The Rust code examples of the explanation, in order.
+    pub examples: Vec<CodeExample>,
+    /// Whether the compiler still emits the error code.
+    pub is_emitted: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CodeExample {
+    /// Whether the example shows the error (it is marked `compile_fail`), or
+    /// code which compiles, e.g. after fixing the error.
+    pub is_erroneous: bool,
+    /// The attributes of the code block, e.g. `compile_fail` and the error code.
+    pub attributes: Vec<String>,
+    /// The code of the example, without the lines hidden from the reader.
+    pub code: String,
+}
+
+impl Explanation {
+    pub fn parse(markdown: &'static str) -> Explanation {
+        let mut examples = vec![];
+        let mut paragraphs: Vec<String> = vec![String::new()];
+        // The attributes and the code of the code block being read, if any.
+        let mut code_block: Option<(Vec<String>, String)> = None;
+
+        for line in markdown.lines() {
+            let dedented_line = line.trim_start();
+            if let Some(info) = dedented_line.strip_prefix("```") {
+                match code_block.take() {
+                    None => {
+                        let attributes = info
+                            .split(',')
+                            .map(|attr| attr.trim())
+                            .filter(|attr| !attr.is_empty())
+                            .map(|attr| attr.to_string())
+                            .collect();
+                        code_block = Some((attributes, String::new()));
+                    }
+                    Some((attributes, code)) => {
+                        if attributes.iter().all(|attr| is_rust_code_block_attribute(attr)) {
+                            let is_erroneous = attributes.iter().any(|attr| attr == "compile_fail");
+                            examples.push(CodeExample { is_erroneous, attributes, code });
+                        }
+                    }
+                }
+                paragraphs.push(String::new());
+            } else if let Some((_, code)) = &mut code_block {
+                // Like rustdoc, hide the lines starting with `# `.
+                if dedented_line != "#" && !dedented_line.starts_with("# ") {
+                    code.push_str(line);
+                    code.push('\n');
+                }
+            } else if dedented_line.is_empty() || dedented_line.starts_with('#') {
+                paragraphs.push(String::new());
+            } else {
+                let paragraph = paragraphs.last_mut().unwrap();
+                if !paragraph.is_empty() {
+                    paragraph.push(' ');
+                }
+                paragraph.push_str(dedented_line);
+            }
+        }
+
+        let first_paragraph = paragraphs.into_iter().find(|p| !p.is_empty()).unwrap_or_default();
+
+        Explanation {
+            title: first_sentence(&first_paragraph).to_string(),
+            markdown,
+            examples,
+            is_emitted: !markdown.contains("this error code is no longer emitted by the compiler"),
+        }
+    }
+}
+
+fn first_sentence(paragraph: &str) -> &str {
+    let mut start = 0;
+    while let Some(i) = paragraph[start..].find(". ") {
+        let end = start + i + 1;
+        // Abbreviations don't end the sentence.
+        if !paragraph[..end].ends_with("e.g.") && !paragraph[..end].ends_with("i.e.") {
+            return &paragraph[..end];
+        }
+        start = end;
+    }
+    paragraph
+}
+
+/// Whether a code block with the attribute `attr` is Rust code, following
+/// the attributes known to rustdoc.
+fn is_rust_code_block_attribute(attr: &str) -> bool {
+    match attr {
+        "rust" | "compile_fail" | "no_run" | "should_panic" | "test_harness" => true,
+        _ => {
+            attr.starts_with("ignore")
+                || attr.starts_with("edition")
+                || (attr.len() == 5
+                    && attr.starts_with('E')
+                    && attr[1..].chars().all(|c| c.is_ascii_digit()))
+        }
+    }
 }
@@ -0,0 +1,72 @@
+use super::*;
+
+const EXPLANATION: &str = r#"A value was used after it was moved, e.g. by a
+function call. This is an error.
+
+Erroneous code example:
+
+```compile_fail,E0999
+fn consume(_: String) {}
+# fn main() {
+let s = String::new();
+consume(s);
+consume(s); // error!
+# }
+```
+
+Clone the value instead:
+
+```
+# fn consume(_: String) {}
+let s = String::new();
+consume(s.clone());
+consume(s);
+```
+
+Or run this command:
+
+```sh
+cargo fix
+```
+"#;
+
+#[test]
+fn explanation_parts() {
+    let explanation = Explanation::parse(EXPLANATION);
+    assert_eq!(
+        explanation.title,
+        "A value was used after it was moved, e.g. by a function call."
+    );
+    assert_eq!(explanation.markdown, EXPLANATION);
+    assert!(explanation.is_emitted);
+    assert_eq!(
+        explanation.examples,
+        [
+            CodeExample {
+                is_erroneous: true,
+                attributes: vec!["compile_fail".to_string(), "E0999".to_string()],
+                code: "fn consume(_: String) {}\n\
+                       let s = String::new();\n\
+                       consume(s);\n\
+                       consume(s); // error!\n"
+                    .to_string(),
+            },
+            CodeExample {
+                is_erroneous: false,
+                attributes: vec![],
+                code: "let s = String::new();\nconsume(s.clone());\nconsume(s);\n".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn explanation_of_removed_code() {
+    let explanation = Explanation::parse(
+        "#### Note: this error code is no longer emitted by the compiler.\n\n\
+         This error was emitted once.\n",
+    );
+    assert_eq!(explanation.title, "This error was emitted once.");
+    assert!(!explanation.is_emitted);
+    assert!(explanation.examples.is_empty());
+}
@@ -0,0 +1,411 @@
+//! A SARIF emitter for errors.
+//!
+//! Unlike the JSON emitter, which prints each diagnostic as soon as it is
+//! emitted, this collects all diagnostics of the session and writes a single
+//! [SARIF 2.1.0] log once the emitter is dropped, which is the format code
+//! scanning tools consume. Every emitted diagnostic becomes a `result` of the
+//! run, error codes and lint names become its `rules`, and suggestions become
+//! `fixes`.
+//!
+//! Warnings about the command line, which are found before the session and
+//! its emitter exist, are buffered in `Options::early_warnings` and emitted
+//! once the session is created, so that only a single log is written.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+use rustc_span::FileName;
+
+use crate::emitter::Emitter;
+use crate::registry::{Explanation, Registry};
+use crate::{CodeSuggestion, Diagnostic, DiagnosticId, Level};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_span::Span;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::mem;
+use std::path::{Component, Path, Prefix};
+use std::thread;
+
+use rustc_serialize::json::{Json, ToJson};
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    /// The `reportingDescriptor`s of the run, one for each diagnostic code
+    /// seen so far.
+    rules: Vec<Json>,
+    /// Maps a diagnostic code to the index of its rule in `rules`.
+    rule_indices: FxHashMap<String, usize>,
+    /// The `result`s of the run, one for each emitted diagnostic.
+    results: Vec<Json>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(registry: Option<Registry>, source_map: Lrc<SourceMap>) -> SarifEmitter {
+        SarifEmitter::new(Box::new(io::BufWriter::new(io::stderr())), registry, source_map)
+    }
+
+    pub fn basic() -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(None, Lrc::new(SourceMap::new(file_path_mapping)))
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst,
+            registry,
+            sm: source_map,
+            rules: Vec::new(),
+            rule_indices: FxHashMap::default(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the rule describing `code`, adding the rule to the
+    /// run if this is the first diagnostic with that code.
+    fn rule_index(&mut self, code: &DiagnosticId) -> usize {
+        let (id, is_lint) = match code {
+            DiagnosticId::Error(code) => (code, false),
+            DiagnosticId::Lint { name, .. } => (name, true),
+        };
+        if let Some(&index) = self.rule_indices.get(id) {
+            return index;
+        }
+
+        let mut properties = vec![("id", id.to_json())];
+        if is_lint {
+            let tags = Json::Array(vec!["lint".to_json()]);
+            properties.push(("properties", object(vec![("tags", tags)])));
+        } else {
+            let help_uri = format!("https://doc.rust-lang.org/error-index.html#{}", id);
+            properties.push(("helpUri", help_uri.to_json()));
+            let explanation = self
+                .registry
+                .as_ref()
+                .and_then(|registry| registry.try_find_description(id).ok())
+                .flatten();
+            if let Some(explanation) = explanation {
+                let title = Explanation::parse(explanation).title;
+                properties.push(("shortDescription", message(&title)));
+                properties.push((
+                    "fullDescription",
+                    object(vec![
+                        ("text", explanation.to_json()),
+                        ("markdown", explanation.to_json()),
+                    ]),
+                ));
+            }
+        }
+
+        let index = self.rules.len();
+        self.rules.push(object(properties));
+        self.rule_indices.insert(id.clone(), index);
+        index
+    }
+
+    fn result(&mut self, diag: &Diagnostic) -> Json {
+        let mut text = diag.message();
+        let mut locations = vec![];
+        let mut related_locations = vec![];
+
+        for span_label in diag.span.span_labels() {
+            if span_label.span.is_dummy() {
+                continue;
+            }
+            let location = self.location(span_label.span, span_label.label.as_deref());
+            if span_label.is_primary {
+                locations.push(location);
+            } else {
+                related_locations.push(location);
+            }
+        }
+
+        // SARIF has no notion of sub-diagnostics, so they are attached to the
+        // result as related locations or, if they have no span, as additional
+        // lines of its message.
+        for child in &diag.children {
+            let child_message = format!("{}: {}", child.level.to_str(), child.message());
+            let span = child.render_span.as_ref().unwrap_or(&child.span);
+            let spans: Vec<Span> =
+                span.primary_spans().iter().copied().filter(|sp| !sp.is_dummy()).collect();
+            if spans.is_empty() {
+                text.push('\n');
+                text.push_str(&child_message);
+            }
+            for sp in spans {
+                related_locations.push(self.location(sp, Some(&child_message)));
+            }
+        }
+
+        let fixes: Vec<Json> = diag.suggestions.iter().flat_map(|sugg| self.fixes(sugg)).collect();
+
+        let mut properties = vec![];
+        if let Some(code) = &diag.code {
+            let id = match code {
+                DiagnosticId::Error(code) => code,
+                DiagnosticId::Lint { name, .. } => name,
+            };
+            properties.push(("ruleId", id.to_json()));
+            properties.push(("ruleIndex", self.rule_index(code).to_json()));
+        }
+        properties.push(("level", sarif_level(diag.level).to_json()));
+        properties.push(("message", message(&text)));
+        properties.push(("locations", Json::Array(locations)));
+        if !related_locations.is_empty() {
+            properties.push(("relatedLocations", Json::Array(related_locations)));
+        }
+        if !fixes.is_empty() {
+            properties.push(("fixes", Json::Array(fixes)));
+        }
+        object(properties)
+    }
+
+    /// Converts each substitution of `suggestion` to a SARIF `fix`.
+    fn fixes(&self, suggestion: &CodeSuggestion) -> Vec<Json> {
+        suggestion
+            .substitutions
+            .iter()
+            .map(|substitution| {
+                // SARIF groups the replacements of a fix by the file they apply to.
+                let mut changes: Vec<(String, Vec<Json>)> = vec![];
+                for part in &substitution.parts {
+                    if part.span.is_dummy() {
+                        continue;
+                    }
+                    let uri = self.artifact_uri(part.span);
+                    let replacement = object(vec![
+                        ("deletedRegion", self.region(part.span)),
+                        ("insertedContent", object(vec![("text", part.snippet.to_json())])),
+                    ]);
+                    match changes.iter_mut().find(|(file, _)| *file == uri) {
+                        Some((_, replacements)) => replacements.push(replacement),
+                        None => changes.push((uri, vec![replacement])),
+                    }
+                }
+                let artifact_changes = changes
+                    .into_iter()
+                    .map(|(uri, replacements)| {
+                        object(vec![
+                            ("artifactLocation", object(vec![("uri", uri.to_json())])),
+                            ("replacements", Json::Array(replacements)),
+                        ])
+                    })
+                    .collect();
+                object(vec![
+                    ("description", message(&suggestion.msg)),
+                    ("artifactChanges", Json::Array(artifact_changes)),
+                    (
+                        "properties",
+                        object(vec![(
+                            "applicability",
+                            format!("{:?}", suggestion.applicability).to_json(),
+                        )]),
+                    ),
+                ])
+            })
+            .collect()
+    }
+
+    fn location(&self, span: Span, label: Option<&str>) -> Json {
+        let mut properties = vec![(
+            "physicalLocation",
+            object(vec![
+                ("artifactLocation", object(vec![("uri", self.artifact_uri(span).to_json())])),
+                ("region", self.region(span)),
+            ]),
+        )];
+        if let Some(label) = label {
+            properties.push(("message", message(label)));
+        }
+        object(properties)
+    }
+
+    /// Returns the URI of the file containing `span`, see `path_to_uri`. Names
+    /// of sources which aren't files, like `<anon>`, are percent-encoded into
+    /// a relative reference as well.
+    fn artifact_uri(&self, span: Span) -> String {
+        match self.sm.lookup_source_file(span.lo()).name {
+            FileName::Real(ref name) => path_to_uri(name.local_path()),
+            FileName::DocTest(ref path, _) => path_to_uri(path),
+            ref name => {
+                let mut uri = String::new();
+                percent_encode(&name.to_string(), &mut uri);
+                uri
+            }
+        }
+    }
+
+    fn region(&self, span: Span) -> Json {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        object(vec![
+            ("startLine", start.line.to_json()),
+            ("startColumn", (start.col.0 + 1).to_json()),
+            ("endLine", end.line.to_json()),
+            ("endColumn", (end.col.0 + 1).to_json()),
+            ("byteOffset", byte_start.to_json()),
+            ("byteLength", (byte_end - byte_start).to_json()),
+        ])
+    }
+
+    fn log(&mut self) -> Json {
+        let driver = object(vec![
+            ("name", "rustc".to_json()),
+            ("informationUri", "https://www.rust-lang.org/".to_json()),
+            ("rules", Json::Array(mem::take(&mut self.rules))),
+        ]);
+        let run = object(vec![
+            ("tool", object(vec![("driver", driver)])),
+            // Columns are computed by `SourceMap::lookup_char_pos`, in chars.
+            ("columnKind", "unicodeCodePoints".to_json()),
+            ("results", Json::Array(mem::take(&mut self.results))),
+        ]);
+        object(vec![
+            ("$schema", SARIF_SCHEMA.to_json()),
+            ("version", SARIF_VERSION.to_json()),
+            ("runs", Json::Array(vec![run])),
+        ])
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &Diagnostic) {
+        // Failure notes like "aborting due to previous error" summarize the
+        // session for humans, and are not results of their own.
+        if diag.level == Level::FailureNote {
+            return;
+        }
+        let result = self.result(diag);
+        self.results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+
+    // The summary is meant for humans, and would become results of the run.
+    fn should_show_error_count(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        let log = self.log();
+        let result = writeln!(&mut self.dst, "{}", log.pretty()).and_then(|_| self.dst.flush());
+        if let Err(e) = result {
+            // Don't turn an ongoing panic into an abort.
+            if !thread::panicking() {
+                panic!("failed to print diagnostics: {:?}", e);
+            }
+        }
+    }
+}
+
+fn object(properties: Vec<(&str, Json)>) -> Json {
+    Json::Object(properties.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+/// A SARIF `message` object with the plain text `text`.
+fn message(text: &str) -> Json {
+    object(vec![("text", text.to_json())])
+}
+
+/// Converts `path` to a URI. An absolute path becomes a `file` URI, with the
+/// drive letter of a Windows path as its first segment (`file:///C:/dir/a.rs`)
+/// and the server of a UNC path as its authority (`file://server/share/a.rs`).
+/// A relative path becomes a relative reference, which SARIF consumers resolve
+/// against the directory the compiler was run in.
+fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::new();
+    let mut is_absolute = false;
+    let mut segments = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                is_absolute = true;
+                match prefix.kind() {
+                    Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                        uri.push_str("file:///");
+                        uri.push(char::from(letter));
+                        uri.push(':');
+                    }
+                    Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                        uri.push_str("file://");
+                        percent_encode(&server.to_string_lossy(), &mut uri);
+                        uri.push('/');
+                        percent_encode(&share.to_string_lossy(), &mut uri);
+                    }
+                    Prefix::Verbatim(name) | Prefix::DeviceNS(name) => {
+                        uri.push_str("file:///");
+                        percent_encode(&name.to_string_lossy(), &mut uri);
+                    }
+                }
+            }
+            Component::RootDir => {
+                if !is_absolute {
+                    uri.push_str("file://");
+                    is_absolute = true;
+                }
+            }
+            Component::CurDir => {}
+            Component::ParentDir => segments.push(Cow::Borrowed("..")),
+            Component::Normal(segment) => segments.push(segment.to_string_lossy()),
+        }
+    }
+    for (i, segment) in segments.iter().enumerate() {
+        if is_absolute || i > 0 {
+            uri.push('/');
+        }
+        percent_encode(segment, &mut uri);
+    }
+    uri
+}
+
+/// Appends `s` to `uri`, percent-encoding every byte which isn't an unreserved
+/// character of RFC 3986. This also encodes `:` in the first segment of a
+/// relative reference, which would otherwise be parsed as a scheme.
+fn percent_encode(s: &str, uri: &mut String) {
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                uri.push(char::from(byte))
+            }
+            _ => write!(uri, "%{:02X}", byte).unwrap(),
+        }
+    }
+}
+
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::Fatal | Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note
+        | Level::Help
+        | Level::FailureNote
+        | Level::Allow
+        | Level::Expect
+        | Level::Cancelled => "note",
+    }
+}
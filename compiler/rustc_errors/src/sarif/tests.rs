@@ -0,0 +1,144 @@
+use super::*;
+
+use crate::registry::Registry;
+use crate::{Applicability, Handler};
+use rustc_serialize::json::from_str;
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+use rustc_span::{BytePos, Span};
+
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+fn with_default_session_globals(f: impl FnOnce()) {
+    let session_globals = rustc_span::SessionGlobals::new(rustc_span::edition::DEFAULT_EDITION);
+    rustc_span::SESSION_GLOBALS.set(&session_globals, f);
+}
+
+/// Emits the diagnostics produced by `f` into a SARIF log for a file
+/// containing `code`, and returns the parsed log.
+fn sarif_log(code: &str, f: impl FnOnce(&Handler)) -> Json {
+    let mut log = None;
+    with_default_session_globals(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let registry = Registry::new(&[("E0999", Some("Something went wrong.\n\nDetails."))]);
+        let se = SarifEmitter::new(Box::new(Shared { data: output.clone() }), Some(registry), sm);
+
+        // The log is written when the emitter, owned by the handler, is dropped.
+        let handler = Handler::with_emitter(true, None, Box::new(se));
+        f(&handler);
+        drop(handler);
+
+        let bytes = output.lock().unwrap();
+        log = Some(from_str(str::from_utf8(&bytes).unwrap()).unwrap());
+    });
+    log.unwrap()
+}
+
+fn path<'a>(json: &'a Json, keys: &[&str]) -> &'a Json {
+    keys.iter().fold(json, |json, key| match key.parse::<usize>() {
+        Ok(index) => &json.as_array().unwrap()[index],
+        Err(_) => json.find(key).unwrap_or_else(|| panic!("missing key `{}`", key)),
+    })
+}
+
+#[test]
+fn empty_log() {
+    let log = sarif_log("", |_| {});
+    assert_eq!(path(&log, &["version"]).as_string(), Some("2.1.0"));
+    assert_eq!(path(&log, &["runs", "0", "tool", "driver", "name"]).as_string(), Some("rustc"));
+    assert!(path(&log, &["runs", "0", "results"]).as_array().unwrap().is_empty());
+}
+
+#[test]
+fn result_with_rule_and_fix() {
+    let log = sarif_log("fn main() {\n    let x = 1;\n}\n", |handler| {
+        let span = Span::with_root_ctxt(BytePos(20), BytePos(21));
+        handler
+            .struct_span_err(span, "bad binding")
+            .code(DiagnosticId::Error("E0999".to_owned()))
+            .span_suggestion(span, "rename it", "_x".to_owned(), Applicability::MachineApplicable)
+            .emit();
+    });
+
+    let run = path(&log, &["runs", "0"]);
+    assert_eq!(path(run, &["tool", "driver", "rules", "0", "id"]).as_string(), Some("E0999"));
+    assert_eq!(
+        path(run, &["tool", "driver", "rules", "0", "shortDescription", "text"]).as_string(),
+        Some("Something went wrong."),
+    );
+
+    let result = path(run, &["results", "0"]);
+    assert_eq!(path(result, &["ruleId"]).as_string(), Some("E0999"));
+    assert_eq!(path(result, &["ruleIndex"]).as_u64(), Some(0));
+    assert_eq!(path(result, &["level"]).as_string(), Some("error"));
+    assert_eq!(path(result, &["message", "text"]).as_string(), Some("bad binding"));
+
+    let location = path(result, &["locations", "0", "physicalLocation"]);
+    assert_eq!(path(location, &["artifactLocation", "uri"]).as_string(), Some("test.rs"));
+    assert_eq!(path(location, &["region", "startLine"]).as_u64(), Some(2));
+    assert_eq!(path(location, &["region", "startColumn"]).as_u64(), Some(9));
+    assert_eq!(path(location, &["region", "endColumn"]).as_u64(), Some(10));
+
+    let fix = path(result, &["fixes", "0"]);
+    assert_eq!(path(fix, &["description", "text"]).as_string(), Some("rename it"));
+    assert_eq!(
+        path(fix, &["properties", "applicability"]).as_string(),
+        Some("MachineApplicable"),
+    );
+    let replacement = path(fix, &["artifactChanges", "0", "replacements", "0"]);
+    assert_eq!(path(replacement, &["deletedRegion", "byteOffset"]).as_u64(), Some(20));
+    assert_eq!(path(replacement, &["deletedRegion", "byteLength"]).as_u64(), Some(1));
+    assert_eq!(path(replacement, &["insertedContent", "text"]).as_string(), Some("_x"));
+}
+
+#[test]
+fn relative_path_uri() {
+    assert_eq!(path_to_uri(Path::new("src/main.rs")), "src/main.rs");
+    assert_eq!(path_to_uri(Path::new("./src/../lib.rs")), "src/../lib.rs");
+    assert_eq!(path_to_uri(Path::new("my crate/a+b.rs")), "my%20crate/a%2Bb.rs");
+    assert_eq!(path_to_uri(Path::new("c:d.rs")), "c%3Ad.rs");
+}
+
+#[test]
+#[cfg(unix)]
+fn absolute_path_uri() {
+    assert_eq!(path_to_uri(Path::new("/home/user/src/main.rs")), "file:///home/user/src/main.rs");
+    assert_eq!(path_to_uri(Path::new("/tmp/100%.rs")), "file:///tmp/100%25.rs");
+}
+
+#[test]
+#[cfg(windows)]
+fn absolute_path_uri() {
+    assert_eq!(path_to_uri(Path::new(r"C:\src\main.rs")), "file:///C:/src/main.rs");
+    assert_eq!(path_to_uri(Path::new(r"\\?\C:\src\main.rs")), "file:///C:/src/main.rs");
+    assert_eq!(path_to_uri(Path::new(r"\\server\share\main.rs")), "file://server/share/main.rs");
+}
+
+#[test]
+fn anonymous_source_uri() {
+    with_default_session_globals(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        let file = sm.new_source_file(FileName::Anon(0), "fn main() {}".to_owned());
+        let se = SarifEmitter::new(Box::new(io::sink()), None, sm);
+        let span = Span::with_root_ctxt(file.start_pos, file.start_pos);
+        assert_eq!(se.artifact_uri(span), "%3Canon%3E");
+    });
+}
@@ -315,6 +315,12 @@ macro_rules! experimental {
         )
     ),
 
+    // Lints:
+    gated!(
+        expect, Normal, template!(List: r#"lint1, lint2, ..., /*opt*/ reason = "...""#),
+        lint_reasons, experimental!(expect)
+    ),
+
     // Testing:
     gated!(allow_fail, Normal, template!(Word), experimental!(allow_fail)),
     gated!(
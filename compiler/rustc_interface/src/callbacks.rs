@@ -26,15 +26,13 @@ fn span_debug(span: rustc_span::Span, f: &mut fmt::Formatter<'_>) -> fmt::Result
 }
 
 /// This is a callback from librustc_ast as it cannot access the implicit state
-/// in librustc_middle otherwise. It is used to when diagnostic messages are
-/// emitted and stores them in the current query, if there is one.
+/// in librustc_middle otherwise. It is used when diagnostic messages are
+/// emitted and records them as side effects of the current dep-graph task, if
+/// there is one.
 fn track_diagnostic(diagnostic: &Diagnostic) {
-    tls::with_context_opt(|icx| {
-        if let Some(icx) = icx {
-            if let Some(ref diagnostics) = icx.diagnostics {
-                let mut diagnostics = diagnostics.lock();
-                diagnostics.extend(Some(diagnostic.clone()));
-            }
+    tls::with_opt(|tcx| {
+        if let Some(tcx) = tcx {
+            tcx.dep_graph.record_diagnostic(diagnostic);
         }
     })
 }
@@ -191,6 +191,7 @@ pub fn create_compiler_and_run<R>(config: Config, f: impl FnOnce(&Compiler) -> R
     rustc_span::with_source_map(compiler.sess.parse_sess.clone_source_map(), move || {
         let r = {
             let _sess_abort_error = OnDrop(|| {
+                compiler.sess.write_time_passes_json();
                 compiler.sess.finish_diagnostics(registry);
             });
 
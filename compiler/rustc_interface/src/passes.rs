@@ -1028,6 +1028,9 @@ pub fn start_codegen<'tcx>(
         rustc_symbol_mangling::test::report_symbol_names(tcx);
     }
 
+    // This must run after codegen, which emits the lints of the optimized MIR.
+    tcx.sess.time("lint_expectation_checking", || rustc_lint::check_expectations(tcx));
+
     tcx.sess.time("assert_dep_graph", || rustc_incremental::assert_dep_graph(tcx));
     tcx.sess.time("serialize_dep_graph", || rustc_incremental::save_dep_graph(tcx));
 
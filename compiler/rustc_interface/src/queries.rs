@@ -167,7 +167,10 @@ pub fn crate_name(&self) -> Result<&Query<String>> {
                 let parse_result = self.parse()?;
                 let krate = parse_result.peek();
                 // parse `#[crate_name]` even if `--crate-name` was passed, to make sure it matches.
-                find_crate_name(self.session(), &krate.attrs, &self.compiler.input)
+                let crate_name =
+                    find_crate_name(self.session(), &krate.attrs, &self.compiler.input);
+                self.session().prof.set_time_passes_crate_name(&crate_name);
+                crate_name
             })
         })
     }
@@ -435,6 +438,10 @@ pub fn enter<F, T>(&self, f: F) -> T
             if self.session().opts.debugging_opts.query_stats {
                 gcx.enter(rustc_query_impl::print_stats);
             }
+
+            if let Some(top_n) = self.session().opts.debugging_opts.self_profile_summary {
+                gcx.enter(|tcx| rustc_query_impl::print_self_profile_summary(tcx, top_n));
+            }
         }
 
         _timer = Some(self.session().timer("free_global_ctxt"));
@@ -330,6 +330,7 @@ pub fn check_lint_name_cmdline(&self, sess: &Session, lint_name: &str, level: Le
                 "requested on the command line with `{} {}`",
                 match level {
                     Level::Allow => "-A",
+                    Level::Expect => unreachable!(),
                     Level::Warn => "-W",
                     Level::Deny => "-D",
                     Level::Forbid => "-F",
@@ -0,0 +1,83 @@
+//! Checking of lint expectations (RFC 2383).
+//!
+//! A lint at the `expect` level is suppressed like an allowed lint, and
+//! `struct_lint_level` emits a diagnostic at the `Expect` level which marks the
+//! `#[expect]` attribute that set the level as fulfilled in the diagnostic
+//! handler. Going through the handler records the fulfillment as a side effect
+//! of the current dep-graph task, like any other diagnostic, so incremental
+//! compilation replays it when the task's result is reused.
+//!
+//! Once all lints of the crate have been emitted, which is only the case after
+//! codegen, every expectation that was never fulfilled is reported with the
+//! `unfulfilled_lint_expectations` lint.
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::lint::{struct_lint_level, LintExpectation, LintSet};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::lint::builtin::UNFULFILLED_LINT_EXPECTATIONS;
+use rustc_session::lint::Level;
+
+/// Reports the unfulfilled lint expectations of the crate. This must run after
+/// codegen, as some lints are only emitted then, see `Lint::emitted_during_codegen`.
+/// When there is no codegen, e.g. for `--emit=metadata` as used by `cargo check`,
+/// these lints never run, so expectations of them can't be fulfilled and are not
+/// reported either.
+pub fn check_expectations(tcx: TyCtxt<'_>) {
+    if !tcx.sess.features_untracked().lint_reasons {
+        return;
+    }
+
+    let lint_levels = tcx.lint_levels(LOCAL_CRATE);
+    let fulfilled = tcx.sess.diagnostic().fulfilled_expectations();
+    let codegen_lints_ran = tcx.sess.opts.output_types.should_codegen();
+
+    // Only expectations of codegen lints can be skipped: an `#[expect]` of a lint
+    // group is checked as long as the group contains another lint.
+    let is_checkable = |expectation: &LintExpectation| {
+        if codegen_lints_ran {
+            return true;
+        }
+        match lint_levels.sets.list[expectation.lint_set as usize] {
+            LintSet::Node { ref specs, .. } => specs.iter().any(|(id, &(level, src))| {
+                level == Level::Expect
+                    && src.span() == expectation.span
+                    && !id.lint.emitted_during_codegen
+            }),
+            LintSet::CommandLine { .. } => true,
+        }
+    };
+
+    // Attributes duplicated by macro expansion share their span and are only
+    // reported once.
+    let mut seen = FxHashSet::default();
+    for expectation in &lint_levels.expectations {
+        if fulfilled.contains(&expectation.span)
+            || !seen.insert(expectation.span)
+            || !is_checkable(expectation)
+        {
+            continue;
+        }
+
+        let (level, src) = lint_levels.sets.get_lint_level(
+            UNFULFILLED_LINT_EXPECTATIONS,
+            expectation.lint_set,
+            None,
+            tcx.sess,
+        );
+        struct_lint_level(
+            tcx.sess,
+            UNFULFILLED_LINT_EXPECTATIONS,
+            level,
+            src,
+            Some(expectation.span.into()),
+            |lint| {
+                let mut diag = lint.build("this lint expectation is unfulfilled");
+                if let Some(rationale) = expectation.reason {
+                    diag.note(&rationale.as_str());
+                }
+                diag.emit();
+            },
+        );
+    }
+}
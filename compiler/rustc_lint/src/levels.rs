@@ -13,7 +13,7 @@
 use rustc_middle::lint::LevelAndSource;
 use rustc_middle::lint::LintDiagnosticBuilder;
 use rustc_middle::lint::{
-    struct_lint_level, LintLevelMap, LintLevelSets, LintLevelSource, LintSet,
+    struct_lint_level, LintExpectation, LintLevelMap, LintLevelSets, LintLevelSource, LintSet,
 };
 use rustc_middle::ty::query::Providers;
 use rustc_middle::ty::TyCtxt;
@@ -49,10 +49,22 @@ fn lint_levels(tcx: TyCtxt<'_>, cnum: CrateNum) -> LintLevelMap {
     builder.levels.build_map()
 }
 
+/// Returns the level of every registered lint as set by the command line, that
+/// is after `-A`, `-W`, `-D`, `-F` and `--cap-lints`, but before any lint
+/// attribute of the crate is taken into account.
+pub fn command_line_lint_levels(
+    sess: &Session,
+    store: &LintStore,
+) -> Vec<(&'static Lint, LevelAndSource)> {
+    let builder = LintLevelsBuilder::new(sess, false, store);
+    store.get_lints().iter().map(|&lint| (lint, builder.lint_level(lint))).collect()
+}
+
 pub struct LintLevelsBuilder<'s> {
     sess: &'s Session,
     sets: LintLevelSets,
     id_to_set: FxHashMap<HirId, u32>,
+    expectations: Vec<LintExpectation>,
     cur: u32,
     warn_about_weird_lints: bool,
     store: &'s LintStore,
@@ -70,6 +82,7 @@ pub fn new(sess: &'s Session, warn_about_weird_lints: bool, store: &'s LintStore
             sets: LintLevelSets::new(),
             cur: 0,
             id_to_set: Default::default(),
+            expectations: Vec::new(),
             warn_about_weird_lints,
             store,
         };
@@ -212,6 +225,7 @@ fn insert_spec(
     /// * Lint levels will be updated based on the attributes provided
     /// * Lint attributes are validated, e.g., a `#[forbid]` can't be switched to
     ///   `#[allow]`
+    /// * `#[expect]` attributes are recorded as lint expectations
     ///
     /// Don't forget to call `pop`!
     pub(crate) fn push(
@@ -221,6 +235,7 @@ pub(crate) fn push(
         is_crate_node: bool,
     ) -> BuilderPush {
         let mut specs = FxHashMap::default();
+        let mut expectations = Vec::new();
         let sess = self.sess;
         let bad_attr = |span| struct_span_err!(sess, span, E0452, "malformed lint attribute input");
         for attr in attrs {
@@ -329,6 +344,9 @@ pub(crate) fn push(
                             self.check_gated_lint(id, attr.span);
                             self.insert_spec(&mut specs, id, (level, src));
                         }
+                        if level == Level::Expect {
+                            expectations.push((li.span(), reason));
+                        }
                     }
 
                     CheckLintNameResult::Tool(result) => {
@@ -343,6 +361,9 @@ pub(crate) fn push(
                                 for id in ids {
                                     self.insert_spec(&mut specs, *id, (level, src));
                                 }
+                                if level == Level::Expect {
+                                    expectations.push((li.span(), reason));
+                                }
                             }
                             Err((Some(ids), ref new_lint_name)) => {
                                 let lint = builtin::RENAMED_AND_REMOVED_LINTS;
@@ -379,6 +400,9 @@ pub(crate) fn push(
                                 for id in ids {
                                     self.insert_spec(&mut specs, *id, (level, src));
                                 }
+                                if level == Level::Expect {
+                                    expectations.push((li.span(), reason));
+                                }
                             }
                             Err((None, _)) => {
                                 // If Tool(Err(None, _)) is returned, then either the lint does not
@@ -459,6 +483,9 @@ pub(crate) fn push(
                             self.check_gated_lint(id, attr.span);
                             self.insert_spec(&mut specs, id, (level, src));
                         }
+                        if level == Level::Expect {
+                            expectations.push((li.span(), reason));
+                        }
                     }
                 }
             }
@@ -504,6 +531,20 @@ pub(crate) fn push(
             self.sets.list.push(LintSet::Node { specs, parent: prev });
         }
 
+        // Only keep the expectations that weren't rejected by `insert_spec`, e.g.
+        // because of a previous `#[forbid]`.
+        for (span, reason) in expectations {
+            let is_in_effect = match self.sets.list[self.cur as usize] {
+                LintSet::Node { ref specs, .. } => specs
+                    .values()
+                    .any(|&(level, src)| level == Level::Expect && src.span() == span),
+                LintSet::CommandLine { .. } => false,
+            };
+            if is_in_effect {
+                self.expectations.push(LintExpectation { span, reason, lint_set: self.cur });
+            }
+        }
+
         BuilderPush { prev, changed: prev != self.cur }
     }
 
@@ -555,7 +596,11 @@ pub fn build(self) -> LintLevelSets {
     }
 
     pub fn build_map(self) -> LintLevelMap {
-        LintLevelMap { sets: self.sets, id_to_set: self.id_to_set }
+        LintLevelMap {
+            sets: self.sets,
+            id_to_set: self.id_to_set,
+            expectations: self.expectations,
+        }
     }
 }
 
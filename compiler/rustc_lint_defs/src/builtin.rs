@@ -8,6 +8,7 @@
 
 use crate::{declare_lint, declare_lint_pass};
 use rustc_span::edition::Edition;
+use rustc_span::symbol::sym;
 
 declare_lint! {
     /// The `forbidden_lint_groups` lint detects violations of
@@ -240,7 +241,8 @@
     /// will not overflow.
     pub ARITHMETIC_OVERFLOW,
     Deny,
-    "arithmetic operation overflows"
+    "arithmetic operation overflows",
+    emitted_during_codegen
 }
 
 declare_lint! {
@@ -264,7 +266,45 @@
     /// `panic!` or `unreachable!` macro instead in case the panic is intended.
     pub UNCONDITIONAL_PANIC,
     Deny,
-    "operation will cause a panic at runtime"
+    "operation will cause a panic at runtime",
+    emitted_during_codegen
+}
+
+declare_lint! {
+    /// The `panic_or_overflow_across_branches` lint detects an operation that
+    /// will panic or overflow at runtime, where the operands are only known
+    /// by joining the values they have on every path reaching the operation.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// fn get(c: bool) -> u8 {
+    ///     let array = [1u8, 2, 3];
+    ///     let mut i = 3;
+    ///     if c {
+    ///         i = 3;
+    ///     }
+    ///     array[i]
+    /// }
+    /// ```
+    ///
+    /// {{produces}}
+    ///
+    /// ### Explanation
+    ///
+    /// The [`unconditional_panic`] and [`arithmetic_overflow`] lints only
+    /// see values assigned in the same basic block as the operation. The
+    /// compiler also tracks the bits of integer locals that are known on all
+    /// paths, and findings that rely on these are reported by this lint. It is "warn" by default so that code which compiled
+    /// before doesn't start to fail. The findings are expected to be moved
+    /// into the deny-by-default lints in the future.
+    ///
+    /// [`unconditional_panic`]: #unconditional-panic
+    /// [`arithmetic_overflow`]: #arithmetic-overflow
+    pub PANIC_OR_OVERFLOW_ACROSS_BRANCHES,
+    Warn,
+    "operation will panic or overflow at runtime on every path reaching it",
+    emitted_during_codegen
 }
 
 declare_lint! {
@@ -2872,6 +2912,132 @@
     };
 }
 
+declare_lint! {
+    /// The `unfulfilled_lint_expectations` lint detects `#[expect(...)]`
+    /// attributes whose expected lint is never emitted.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// #![feature(lint_reasons)]
+    ///
+    /// #[expect(unused_variables)]
+    /// let x = 10;
+    /// println!("{}", x);
+    /// ```
+    ///
+    /// {{produces}}
+    ///
+    /// ### Explanation
+    ///
+    /// The `#[expect]` attribute suppresses the lint it names, like
+    /// `#[allow]`, but also asserts that the lint is actually emitted in the
+    /// annotated code. When the code changes so that the lint no longer
+    /// fires, the attribute is likely stale and can be removed. This lint
+    /// points out such attributes.
+    ///
+    /// Some lints, like `arithmetic_overflow`, are only emitted during
+    /// codegen. Expectations of these lints are not checked when there is no
+    /// codegen, e.g. with `cargo check`.
+    ///
+    /// The `#[expect]` attribute is unstable, see [issue #54503] for more
+    /// details.
+    ///
+    /// [issue #54503]: https://github.com/rust-lang/rust/issues/54503
+    pub UNFULFILLED_LINT_EXPECTATIONS,
+    Warn,
+    "unfulfilled lint expectation",
+    @feature_gate = sym::lint_reasons;
+}
+
+declare_lint! {
+    /// The `missing_inline_hint` lint detects small exported functions that
+    /// other crates cannot inline because they are not marked `#[inline]`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,compile_fail
+    /// #![crate_type = "lib"]
+    /// #![deny(missing_inline_hint)]
+    ///
+    /// pub fn double(x: u32) -> u32 {
+    ///     x * 2
+    /// }
+    /// ```
+    ///
+    /// {{produces}}
+    ///
+    /// ### Explanation
+    ///
+    /// Without `#[inline]`, the body of a function that isn't generic is only
+    /// available to the crate defining it, so calls from other crates stay
+    /// calls unless LTO is used. For small functions, the call is often more
+    /// expensive than the body. This lint points out exported functions whose
+    /// MIR is below the threshold the MIR inliner uses for `#[inline]`
+    /// functions. It is "allow" by default because it doesn't know how often
+    /// the function is called, and inlining functions that are rarely called
+    /// only adds to compile times.
+    ///
+    /// Functions calling another function of the crate that isn't
+    /// `#[inline]` are not linted, as other crates couldn't inline that call
+    /// either.
+    ///
+    /// This lint is checked while the optimized MIR is built, so it is not
+    /// emitted when there is no codegen, e.g. with `cargo check`. With
+    /// incremental compilation, it is also not emitted again for functions
+    /// whose optimized MIR is reused from a previous build.
+    pub MISSING_INLINE_HINT,
+    Allow,
+    "detects small exported functions that other crates cannot inline",
+    emitted_during_codegen
+}
+
+declare_lint! {
+    /// The `large_stack_usage` lint detects functions that may use more stack
+    /// than the limit given with `-Z stack-usage-limit`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,ignore (needs `-Z stack-usage-limit`)
+    /// fn fill() -> u8 {
+    ///     let buffer = [0u8; 65536];
+    ///     buffer[0]
+    /// }
+    ///
+    /// fn main() {
+    ///     fill();
+    /// }
+    /// ```
+    ///
+    /// With `-Z stack-usage-limit=16384`, this produces:
+    ///
+    /// ```text
+    /// warning: `fill` may use up to 65554 bytes of stack
+    ///  --> lint_example.rs:1:1
+    ///   |
+    /// 1 | fn fill() -> u8 {
+    ///   | ^^^^^^^^^^^^^^^
+    ///   |
+    ///   = note: `#[warn(large_stack_usage)]` on by default
+    ///   = note: its own frame uses 65554 bytes, the limit is 16384 bytes
+    /// ```
+    ///
+    /// ### Explanation
+    ///
+    /// Threads other than the main thread often have small stacks, and large
+    /// locals like byte buffers can overflow them. The estimate is the sum of
+    /// the sizes of the locals of the function, plus the largest estimate of
+    /// the functions it may call in this crate. It is an upper bound as far as
+    /// calls within the crate go, because LLVM can reuse the stack slots of
+    /// locals that are not live at the same time. Calls to other crates are
+    /// not accounted for, and functions that may recurse are not linted, as
+    /// their stack usage has no bound.
+    pub LARGE_STACK_USAGE,
+    Warn,
+    "detects functions that may use more stack than `-Z stack-usage-limit`",
+    emitted_during_codegen
+}
+
 declare_lint_pass! {
     /// Does nothing as a lint pass, but registers some `Lint`s
     /// that are used by other parts of the compiler.
@@ -2880,6 +3046,7 @@
         ILLEGAL_FLOATING_POINT_LITERAL_PATTERN,
         ARITHMETIC_OVERFLOW,
         UNCONDITIONAL_PANIC,
+        PANIC_OR_OVERFLOW_ACROSS_BRANCHES,
         UNUSED_IMPORTS,
         UNUSED_EXTERN_CRATES,
         UNUSED_CRATE_DEPENDENCIES,
@@ -2955,6 +3122,9 @@
         SEMICOLON_IN_EXPRESSIONS_FROM_MACROS,
         DISJOINT_CAPTURE_DROP_REORDER,
         LEGACY_DERIVE_HELPERS,
+        UNFULFILLED_LINT_EXPECTATIONS,
+        MISSING_INLINE_HINT,
+        LARGE_STACK_USAGE,
     ]
 }
 
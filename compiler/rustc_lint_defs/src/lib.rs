@@ -46,6 +46,10 @@ pub enum Applicability {
 #[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
 pub enum Level {
     Allow,
+    /// The lint is expected to be emitted at this location (RFC 2383). The lint
+    /// itself is suppressed, and the `unfulfilled_lint_expectations` lint is
+    /// emitted if it never fires.
+    Expect,
     Warn,
     Deny,
     Forbid,
@@ -58,6 +62,7 @@ impl Level {
     pub fn as_str(self) -> &'static str {
         match self {
             Level::Allow => "allow",
+            Level::Expect => "expect",
             Level::Warn => "warn",
             Level::Deny => "deny",
             Level::Forbid => "forbid",
@@ -79,6 +84,7 @@ pub fn from_str(x: &str) -> Option<Level> {
     pub fn from_symbol(x: Symbol) -> Option<Level> {
         match x {
             sym::allow => Some(Level::Allow),
+            sym::expect => Some(Level::Expect),
             sym::warn => Some(Level::Warn),
             sym::deny => Some(Level::Deny),
             sym::forbid => Some(Level::Forbid),
@@ -131,6 +137,10 @@ pub struct Lint {
     pub feature_gate: Option<Symbol>,
 
     pub crate_level_only: bool,
+
+    /// `true` if this lint is only emitted while building the optimized MIR or the mono items
+    /// for codegen, so it is never emitted when there is no codegen, e.g. for `--emit=metadata`.
+    pub emitted_during_codegen: bool,
 }
 
 /// Extra information for a future incompatibility lint.
@@ -170,6 +180,7 @@ pub const fn default_fields_for_macro() -> Self {
             future_incompatible: None,
             feature_gate: None,
             crate_level_only: false,
+            emitted_during_codegen: false,
         }
     }
 
@@ -468,6 +479,7 @@ macro_rules! declare_tool_lint {
             is_plugin: true,
             feature_gate: None,
             crate_level_only: false,
+            emitted_during_codegen: false,
         };
     );
 }
@@ -904,6 +904,17 @@ fn report_unused_deps(&mut self, krate: &ast::Crate) {
             }
 
             // Got a real unused --extern
+            if self.sess.opts.json_unused_externs {
+                let paths: Vec<String> = entry
+                    .files()
+                    .into_iter()
+                    .flatten()
+                    .map(|path| path.original().display().to_string())
+                    .collect();
+                let paths: Vec<&str> = paths.iter().map(|path| &path[..]).collect();
+                self.sess.parse_sess.span_diagnostic.emit_unused_extern(name, &paths);
+            }
+
             let diag = match self.sess.opts.extern_dep_specs.get(name) {
                 Some(loc) => BuiltinLintDiagnostics::ExternDepSpec(name.clone(), loc.into()),
                 None => {
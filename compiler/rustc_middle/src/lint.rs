@@ -3,7 +3,7 @@
 use crate::ich::StableHashingContext;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
-use rustc_errors::{DiagnosticBuilder, DiagnosticId};
+use rustc_errors::{Diagnostic, DiagnosticBuilder, DiagnosticId};
 use rustc_hir::HirId;
 use rustc_session::lint::{
     builtin::{self, FORBIDDEN_LINT_GROUPS},
@@ -149,10 +149,26 @@ pub fn get_lint_id_level(
     }
 }
 
+/// A lint expectation created by an `#[expect(..)]` attribute (RFC 2383).
+#[derive(Clone, Copy, PartialEq, Eq, HashStable, Debug)]
+pub struct LintExpectation {
+    /// The span of the lint name inside the `#[expect]` attribute. This also
+    /// identifies the expectation when a lint fulfills it, see
+    /// `Handler::fulfilled_expectations`.
+    pub span: Span,
+    /// The RFC 2383 reason given in the attribute, if any.
+    pub reason: Option<Symbol>,
+    /// The lint set in which the expectation was declared. This determines
+    /// the level of the `unfulfilled_lint_expectations` lint.
+    pub lint_set: u32,
+}
+
 #[derive(Debug)]
 pub struct LintLevelMap {
     pub sets: LintLevelSets,
     pub id_to_set: FxHashMap<HirId, u32>,
+    /// All `#[expect]` attributes of the crate, in source order.
+    pub expectations: Vec<LintExpectation>,
 }
 
 impl LintLevelMap {
@@ -176,7 +192,7 @@ pub fn level_and_source(
 impl<'a> HashStable<StableHashingContext<'a>> for LintLevelMap {
     #[inline]
     fn hash_stable(&self, hcx: &mut StableHashingContext<'a>, hasher: &mut StableHasher) {
-        let LintLevelMap { ref sets, ref id_to_set } = *self;
+        let LintLevelMap { ref sets, ref id_to_set, ref expectations } = *self;
 
         id_to_set.hash_stable(hcx, hasher);
 
@@ -202,6 +218,8 @@ fn hash_stable(&self, hcx: &mut StableHashingContext<'a>, hasher: &mut StableHas
                     }
                 }
             }
+
+            expectations.hash_stable(hcx, hasher);
         })
     }
 }
@@ -246,8 +264,21 @@ fn struct_lint_level_impl(
         let has_future_breakage =
             future_incompatible.map_or(false, |incompat| incompat.future_breakage.is_some());
 
+        // A lint at the `expect` level fulfills the expectation that set it, and
+        // is otherwise handled like an allowed lint. The expectation is fulfilled
+        // by emitting a diagnostic, so that it is recorded as a side effect of the
+        // current dep-graph task and replayed when incremental compilation reuses
+        // the task's result instead of emitting the lint again.
+        if level == Level::Expect {
+            if let LintLevelSource::Node(_, expectation_span, _) = src {
+                let mut diag = Diagnostic::new(rustc_errors::Level::Expect, "");
+                diag.set_span(expectation_span);
+                sess.diagnostic().emit_diagnostic(&diag);
+            }
+        }
+
         let mut err = match (level, span) {
-            (Level::Allow, span) => {
+            (Level::Allow | Level::Expect, span) => {
                 if has_future_breakage {
                     if let Some(span) = span {
                         sess.struct_span_allow(span, "")
@@ -299,6 +330,7 @@ fn struct_lint_level_impl(
                     Level::Deny => "-D",
                     Level::Forbid => "-F",
                     Level::Allow => "-A",
+                    Level::Expect => bug!("`expect` lint level set on the command line"),
                 };
                 let hyphen_case_lint_name = name.replace("_", "-");
                 if lint_flag_val.as_str() == name {
@@ -1629,8 +1629,6 @@ pub mod tls {
     use crate::dep_graph::{DepKind, TaskDeps};
     use crate::ty::query;
     use rustc_data_structures::sync::{self, Lock};
-    use rustc_data_structures::thin_vec::ThinVec;
-    use rustc_errors::Diagnostic;
     use std::mem;
 
     #[cfg(not(parallel_compiler))]
@@ -1653,10 +1651,6 @@ pub struct ImplicitCtxt<'a, 'tcx> {
         /// `ty::query::plumbing` when executing a query.
         pub query: Option<query::QueryJobId<DepKind>>,
 
-        /// Where to store diagnostics for the current query job, if any.
-        /// This is updated by `JobOwner::start` in `ty::query::plumbing` when executing a query.
-        pub diagnostics: Option<&'a Lock<ThinVec<Diagnostic>>>,
-
         /// Used to prevent layout from recursing too deeply.
         pub layout_depth: usize,
 
@@ -1668,7 +1662,7 @@ pub struct ImplicitCtxt<'a, 'tcx> {
     impl<'a, 'tcx> ImplicitCtxt<'a, 'tcx> {
         pub fn new(gcx: &'tcx GlobalCtxt<'tcx>) -> Self {
             let tcx = TyCtxt { gcx };
-            ImplicitCtxt { tcx, query: None, diagnostics: None, layout_depth: 0, task_deps: None }
+            ImplicitCtxt { tcx, query: None, layout_depth: 0, task_deps: None }
         }
     }
 
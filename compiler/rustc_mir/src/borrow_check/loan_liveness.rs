@@ -0,0 +1,285 @@
+//! Computes where loans are live with a location-sensitive analysis, for
+//! `-Z location-sensitive-borrowck`. See [`LoanLiveness`].
+
+use rustc_index::bit_set::{BitSet, SparseBitMatrix};
+use rustc_index::vec::{Idx, IndexVec};
+use rustc_middle::mir::{Body, Location};
+use rustc_middle::ty::RegionVid;
+
+use crate::borrow_check::region_infer::values::{PointIndex, RegionValueElements};
+use crate::borrow_check::{BorrowSet, RegionInferenceContext, ToRegionVid};
+use crate::dataflow::indexes::BorrowIndex;
+
+/// The largest `points * regions^2` of a body the analysis runs on, see [`LoanLiveness`].
+const MAX_SUBSET_ELEMENTS: usize = 1 << 28;
+
+/// The points where each loan is live, computed like Polonius does.
+///
+/// NLL considers a loan live at every point of the region of its borrow. That region contains
+/// all the points where the regions the loan flows into are live, even if the loan only flows
+/// into them on some paths. This rejects "NLL problem case #3":
+///
+/// ```ignore (illustrative)
+/// fn get_default<'r>(map: &'r mut HashMap<u32, String>, key: u32) -> &'r mut String {
+///     match map.get_mut(&key) {
+///         Some(value) => value,
+///         None => {
+///             map.insert(key, String::new());
+///             map.get_mut(&key).unwrap()
+///         }
+///     }
+/// }
+/// ```
+///
+/// Returning `value` requires the loan of `*map` to outlive `'r`, which is live in the whole
+/// body, so the loan is live in the `None` arm, where `map` is borrowed again.
+///
+/// This analysis instead tracks which regions contain the loan at each point:
+///
+/// - the region of a borrow contains its loan at the point of the borrow;
+/// - where an outlives constraint `'a: 'b` holds, the loans contained in `'a` are also contained
+///   in `'b`;
+/// - a region still contains a loan at the successors of a point where it contains it, if the
+///   region is live there.
+///
+/// An outlives constraint holds at the point it arises from, or everywhere if it comes from a
+/// `Locations::All`. It also keeps holding at the successors of a point where it holds, if both
+/// its regions are live there: a reference to a region can be stored and read back later.
+///
+/// A loan is then live at the points where a live region contains it. In the example, the loan
+/// only flows into `'r` in the `Some` arm. The regions inferred by NLL are still used to report
+/// region errors, and a loan is only considered live at points its region contains.
+///
+/// The constraints holding at each point can relate every pair of regions, so they take
+/// `O(points * regions^2)` space, and carrying them over to the successors of each point until
+/// nothing changes takes more time than that. For bodies where that bound exceeds
+/// `MAX_SUBSET_ELEMENTS`, the analysis isn't run, and the loans are live wherever NLL considers
+/// them live.
+crate struct LoanLiveness {
+    elements: RegionValueElements,
+
+    /// The points where each loan is live.
+    live_points: SparseBitMatrix<BorrowIndex, PointIndex>,
+}
+
+impl LoanLiveness {
+    /// Computes where the loans of `borrow_set` are live, or returns `None` if `body` is too
+    /// large for the analysis.
+    crate fn compute(
+        body: &Body<'_>,
+        regioncx: &RegionInferenceContext<'_>,
+        borrow_set: &BorrowSet<'_>,
+    ) -> Option<Self> {
+        let elements = RegionValueElements::new(body);
+        let num_regions = regioncx.regions().count();
+        let subset_elements = elements
+            .num_points()
+            .checked_mul(num_regions)
+            .and_then(|n| n.checked_mul(num_regions))
+            .filter(|&n| n <= MAX_SUBSET_ELEMENTS);
+        if subset_elements.is_none() {
+            debug!(
+                "LoanLiveness::compute: {} points and {} regions are too many, using NLL",
+                elements.num_points(),
+                num_regions
+            );
+            return None;
+        }
+
+        let cx = LivenessContext { body, elements: &elements, regioncx, num_regions };
+        let subsets = cx.compute_subsets();
+
+        let mut live_points = SparseBitMatrix::new(elements.num_points());
+        for (borrow_index, borrow_data) in borrow_set.iter_enumerated() {
+            let point = elements.point_from_location(borrow_data.reserve_location);
+            let region = borrow_data.region.to_region_vid();
+            cx.compute_live_points(&subsets, borrow_index, point, region, &mut live_points);
+        }
+
+        Some(LoanLiveness { elements, live_points })
+    }
+
+    /// Returns `true` if the loan of `borrow_index` is live at `location`.
+    crate fn is_live_at(&self, borrow_index: BorrowIndex, location: Location) -> bool {
+        self.live_points.contains(borrow_index, self.elements.point_from_location(location))
+    }
+}
+
+/// The outlives constraints holding at each point, as edges from the longer region to the
+/// shorter one. This is the direction loans flow in.
+struct Subsets {
+    /// The constraints holding everywhere.
+    everywhere: IndexVec<RegionVid, Vec<RegionVid>>,
+
+    /// The other constraints holding at each point.
+    at_point: IndexVec<PointIndex, SparseBitMatrix<RegionVid, RegionVid>>,
+}
+
+impl Subsets {
+    /// Adds to `regions` all the regions that loans contained in `regions` flow into at `point`.
+    fn close(&self, point: PointIndex, regions: &mut BitSet<RegionVid>) {
+        let mut stack: Vec<_> = regions.iter().collect();
+        while let Some(longer) = stack.pop() {
+            let at_point = self.at_point[point].iter(longer);
+            for shorter in self.everywhere[longer].iter().copied().chain(at_point) {
+                if regions.insert(shorter) {
+                    stack.push(shorter);
+                }
+            }
+        }
+    }
+}
+
+struct LivenessContext<'a, 'tcx> {
+    body: &'a Body<'tcx>,
+    elements: &'a RegionValueElements,
+    regioncx: &'a RegionInferenceContext<'tcx>,
+    num_regions: usize,
+}
+
+impl LivenessContext<'_, '_> {
+    fn is_live_at(&self, region: RegionVid, point: PointIndex) -> bool {
+        self.regioncx.is_live_at(region, self.elements.to_location(point))
+    }
+
+    /// Returns the points control flows to after `point`, including along unwind edges.
+    fn successors(&self, point: PointIndex) -> Vec<PointIndex> {
+        let location = self.elements.to_location(point);
+        let block_data = &self.body[location.block];
+        if location.statement_index < block_data.statements.len() {
+            vec![PointIndex::new(point.index() + 1)]
+        } else {
+            block_data.terminator().successors().map(|&bb| self.elements.entry_point(bb)).collect()
+        }
+    }
+
+    fn compute_subsets(&self) -> Subsets {
+        let mut everywhere = IndexVec::from_elem_n(Vec::new(), self.num_regions);
+        let mut at_point: IndexVec<PointIndex, _> = (0..self.elements.num_points())
+            .map(|_| SparseBitMatrix::new(self.num_regions))
+            .collect();
+
+        let mut worklist = Vec::new();
+        for constraint in self.regioncx.outlives_constraints() {
+            match constraint.locations.from_location() {
+                Some(location) => {
+                    let point = self.elements.point_from_location(location);
+                    at_point[point].insert(constraint.sup, constraint.sub);
+                    worklist.push(point);
+                }
+                None => everywhere[constraint.sup].push(constraint.sub),
+            }
+        }
+        let everywhere_longer: Vec<RegionVid> = everywhere
+            .iter_enumerated()
+            .filter(|(_, edges)| !edges.is_empty())
+            .map(|(r, _)| r)
+            .collect();
+        let mut subsets = Subsets { everywhere, at_point };
+
+        // Carry the constraints holding at each point over to its successors, until nothing
+        // changes. Loans can flow through a region that is dead at the successor, so the
+        // constraints carried over are the paths between its live regions. The paths only made
+        // of constraints holding everywhere don't need to be carried over.
+        while let Some(point) = worklist.pop() {
+            let mut carried = Vec::new();
+            for successor in self.successors(point) {
+                let longer_regions =
+                    subsets.at_point[point].rows().chain(everywhere_longer.iter().copied());
+                for longer in longer_regions {
+                    if !self.is_live_at(longer, successor) {
+                        continue;
+                    }
+                    for shorter in self.reachable_through_point(&subsets, point, longer).iter() {
+                        if shorter != longer && self.is_live_at(shorter, successor) {
+                            carried.push((successor, longer, shorter));
+                        }
+                    }
+                }
+            }
+
+            for (successor, longer, shorter) in carried {
+                if subsets.at_point[successor].insert(longer, shorter) {
+                    worklist.push(successor);
+                }
+            }
+        }
+
+        subsets
+    }
+
+    /// Returns the regions reachable from `longer` at `point` along paths that go through at
+    /// least one constraint that doesn't hold everywhere.
+    fn reachable_through_point(
+        &self,
+        subsets: &Subsets,
+        point: PointIndex,
+        longer: RegionVid,
+    ) -> BitSet<RegionVid> {
+        // Regions reachable through constraints holding everywhere only, and regions reachable
+        // through at least one constraint holding at `point`.
+        let mut everywhere_only = BitSet::new_empty(self.num_regions);
+        let mut through_point = BitSet::new_empty(self.num_regions);
+        let mut stack = vec![(longer, false)];
+        everywhere_only.insert(longer);
+        while let Some((region, is_through_point)) = stack.pop() {
+            for &shorter in &subsets.everywhere[region] {
+                let visited =
+                    if is_through_point { &mut through_point } else { &mut everywhere_only };
+                if visited.insert(shorter) {
+                    stack.push((shorter, is_through_point));
+                }
+            }
+            for shorter in subsets.at_point[point].iter(region) {
+                if through_point.insert(shorter) {
+                    stack.push((shorter, true));
+                }
+            }
+        }
+        through_point
+    }
+
+    /// Adds to `live_points` the points where the loan of `borrow_index`, issued at `point` into
+    /// `region`, is live.
+    fn compute_live_points(
+        &self,
+        subsets: &Subsets,
+        borrow_index: BorrowIndex,
+        point: PointIndex,
+        region: RegionVid,
+        live_points: &mut SparseBitMatrix<BorrowIndex, PointIndex>,
+    ) {
+        // The loan is live where it is issued, and it is already contained in its region there.
+        live_points.insert(borrow_index, point);
+
+        // The regions containing the loan on entry to each point, before following the
+        // constraints holding there.
+        let mut contains = SparseBitMatrix::new(self.num_regions);
+        contains.insert(point, region);
+
+        let mut worklist = vec![point];
+        while let Some(point) = worklist.pop() {
+            let mut regions = BitSet::new_empty(self.num_regions);
+            for region in contains.iter(point) {
+                regions.insert(region);
+            }
+            subsets.close(point, &mut regions);
+
+            if regions.iter().any(|region| self.is_live_at(region, point)) {
+                live_points.insert(borrow_index, point);
+            }
+
+            for successor in self.successors(point) {
+                let mut changed = false;
+                for region in regions.iter() {
+                    if self.is_live_at(region, successor) {
+                        changed |= contains.insert(successor, region);
+                    }
+                }
+                if changed {
+                    worklist.push(successor);
+                }
+            }
+        }
+    }
+}
@@ -51,6 +51,7 @@
 mod diagnostics;
 mod facts;
 mod invalidation;
+mod loan_liveness;
 mod location;
 mod member_constraints;
 mod nll;
@@ -65,6 +66,7 @@
 mod used_muts;
 
 crate use borrow_set::{BorrowData, BorrowSet};
+crate use loan_liveness::LoanLiveness;
 crate use nll::{PoloniusOutput, ToRegionVid};
 crate use place_ext::PlaceExt;
 crate use places_conflict::{places_conflict, PlaceConflictBias};
@@ -243,7 +245,13 @@ fn do_mir_borrowck<'a, 'tcx>(
 
     let regioncx = Rc::new(regioncx);
 
-    let flow_borrows = Borrows::new(tcx, &body, &regioncx, &borrow_set)
+    let loan_liveness = if tcx.sess.opts.debugging_opts.location_sensitive_borrowck {
+        LoanLiveness::compute(&body, &regioncx, &borrow_set)
+    } else {
+        None
+    };
+
+    let flow_borrows = Borrows::new(tcx, &body, &regioncx, loan_liveness.as_ref(), &borrow_set)
         .into_engine(tcx, &body)
         .pass_name("borrowck")
         .iterate_to_fixpoint();
@@ -443,6 +451,9 @@ fn do_mir_borrowck<'a, 'tcx>(
         }
     }
 
+    // Dump the constraint paths the errors above were blamed on, if requested.
+    nll::dump_explanation(tcx, &body, &regioncx);
+
     let result = BorrowCheckResult {
         concrete_opaque_types: opaque_type_values,
         closure_requirements: opt_closure_req,
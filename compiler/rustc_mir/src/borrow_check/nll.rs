@@ -9,7 +9,7 @@
     BasicBlock, Body, ClosureOutlivesSubject, ClosureRegionRequirements, LocalKind, Location,
     Promoted,
 };
-use rustc_middle::ty::{self, RegionKind, RegionVid};
+use rustc_middle::ty::{self, RegionKind, RegionVid, TyCtxt};
 use rustc_span::symbol::sym;
 use std::env;
 use std::fmt::Debug;
@@ -266,6 +266,10 @@ pub(in crate::borrow_check) fn compute_regions<'cx, 'tcx>(
         elements,
     );
 
+    if explain_enabled(infcx.tcx, body.source.def_id()) {
+        regioncx.record_blamed_paths();
+    }
+
     // Generate various additional constraints.
     invalidation::generate_invalidates(infcx.tcx, &mut all_facts, location_table, body, borrow_set);
 
@@ -362,6 +366,39 @@ pub(super) fn dump_mir_results<'a, 'tcx>(
     };
 }
 
+fn explain_enabled(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    let filter = match tcx.sess.opts.debugging_opts.explain_borrowck {
+        None => return false,
+        Some(ref filter) => filter,
+    };
+    // See `mir_util::dump_enabled` for why we force the impl filename.
+    ty::print::with_forced_impl_filename_line(|| tcx.def_path_str(def_id)).contains(filter)
+}
+
+/// For `-Z explain-borrowck`, dump the region constraint graph along with the
+/// constraint paths the borrowck diagnostics were blamed on, as graphviz and JSON.
+pub(super) fn dump_explanation<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    regioncx: &RegionInferenceContext<'tcx>,
+) {
+    if !explain_enabled(tcx, body.source.def_id()) {
+        return;
+    }
+
+    let _: io::Result<()> = try {
+        let mut file =
+            pretty::create_dump_file(tcx, "dot", None, "explain_borrowck", &0, body.source)?;
+        regioncx.dump_graphviz_blamed_constraints(&mut file)?;
+    };
+
+    let _: io::Result<()> = try {
+        let mut file =
+            pretty::create_dump_file(tcx, "json", None, "explain_borrowck", &0, body.source)?;
+        regioncx.dump_json_explanation(tcx, body, &mut file)?;
+    };
+}
+
 pub(super) fn dump_annotation<'a, 'tcx>(
     infcx: &InferCtxt<'a, 'tcx>,
     body: &Body<'tcx>,
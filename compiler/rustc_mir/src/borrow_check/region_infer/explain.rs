@@ -0,0 +1,107 @@
+//! Support for `-Z explain-borrowck`, which dumps the region constraint
+//! graph of a function along with the constraint paths that region errors
+//! and borrow explanations were blamed on.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+use rustc_serialize::json::{Json, ToJson};
+
+use super::*;
+use crate::borrow_check::constraints::OutlivesConstraint;
+
+/// A constraint path found by `best_blame_constraint`.
+#[derive(Clone, Debug)]
+pub(super) struct BlamedPath {
+    /// The constraints from the region being explained to the region it
+    /// was required to outlive.
+    pub(super) constraints: Vec<OutlivesConstraint>,
+
+    /// The index in `constraints` of the constraint the diagnostic points
+    /// at, if one of them was interesting enough.
+    pub(super) blamed: Option<usize>,
+}
+
+impl<'tcx> RegionInferenceContext<'tcx> {
+    /// Start recording the constraint paths that diagnostics are blamed on,
+    /// so that they can be dumped once borrowck is done.
+    crate fn record_blamed_paths(&mut self) {
+        self.blamed_paths = Some(RefCell::new(Vec::new()));
+    }
+
+    /// Returns the constraint paths blamed so far.
+    pub(super) fn blamed_paths(&self) -> Vec<BlamedPath> {
+        self.blamed_paths.as_ref().map_or_else(Vec::new, |paths| paths.borrow().clone())
+    }
+
+    /// Returns all the constraints of the graph, including those that
+    /// only appear in blamed paths (the implicit `'static: 'r` edges).
+    pub(super) fn explained_constraints(
+        &self,
+        blamed_paths: &[BlamedPath],
+    ) -> Vec<OutlivesConstraint> {
+        let mut seen = BTreeSet::new();
+        self.constraints
+            .outlives()
+            .iter()
+            .chain(blamed_paths.iter().flat_map(|path| path.constraints.iter()))
+            .copied()
+            .filter(|&constraint| seen.insert(constraint))
+            .collect()
+    }
+
+    /// Write out the region constraint graph and the blamed paths as JSON.
+    crate fn dump_json_explanation(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        body: &Body<'tcx>,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let constraint_to_json = |constraint: &OutlivesConstraint| {
+            let span = constraint.locations.span(body);
+            let mut object = BTreeMap::new();
+            object.insert("sup".to_string(), format!("{:?}", constraint.sup).to_json());
+            object.insert("sub".to_string(), format!("{:?}", constraint.sub).to_json());
+            object.insert("locations".to_string(), format!("{:?}", constraint.locations).to_json());
+            object.insert("category".to_string(), format!("{:?}", constraint.category).to_json());
+            object.insert("span".to_string(), tcx.sess.source_map().span_to_string(span).to_json());
+            Json::Object(object)
+        };
+
+        let regions = self
+            .definitions
+            .iter_enumerated()
+            .map(|(region, definition)| {
+                let mut object = BTreeMap::new();
+                object.insert("region".to_string(), format!("{:?}", region).to_json());
+                object.insert("origin".to_string(), format!("{:?}", definition.origin).to_json());
+                let external_name = definition.external_name.map(|name| format!("{:?}", name));
+                object.insert("external_name".to_string(), external_name.to_json());
+                let scc = self.constraint_sccs.scc(region);
+                object.insert("scc".to_string(), scc.index().to_json());
+                Json::Object(object)
+            })
+            .collect();
+
+        let blamed_paths = self.blamed_paths();
+        let constraints =
+            self.explained_constraints(&blamed_paths).iter().map(constraint_to_json).collect();
+        let blamed_paths = blamed_paths
+            .iter()
+            .map(|path| {
+                let mut object = BTreeMap::new();
+                let constraints = path.constraints.iter().map(constraint_to_json).collect();
+                object.insert("constraints".to_string(), Json::Array(constraints));
+                object.insert("blamed".to_string(), path.blamed.to_json());
+                Json::Object(object)
+            })
+            .collect();
+
+        let mut object = BTreeMap::new();
+        object.insert("function".to_string(), tcx.def_path_str(body.source.def_id()).to_json());
+        object.insert("regions".to_string(), Json::Array(regions));
+        object.insert("constraints".to_string(), Json::Array(constraints));
+        object.insert("blamed_paths".to_string(), Json::Array(blamed_paths));
+        writeln!(w, "{}", Json::Object(object).pretty())
+    }
+}
@@ -5,6 +5,7 @@
 use std::borrow::Cow;
 use std::io::{self, Write};
 
+use super::explain::BlamedPath;
 use super::*;
 use crate::borrow_check::constraints::OutlivesConstraint;
 use rustc_graphviz as dot;
@@ -27,6 +28,14 @@ impl<'tcx> RegionInferenceContext<'tcx> {
 
         dot::render(&SccConstraints { regioncx: self, nodes_per_scc }, &mut w)
     }
+
+    /// Write out the region constraint graph, with the constraint paths
+    /// blamed by diagnostics in bold.
+    crate fn dump_graphviz_blamed_constraints(&self, mut w: &mut dyn Write) -> io::Result<()> {
+        let blamed_paths = self.blamed_paths();
+        let constraints = self.explained_constraints(&blamed_paths);
+        dot::render(&BlamedConstraints { regioncx: self, constraints, blamed_paths }, &mut w)
+    }
 }
 
 struct RawConstraints<'a, 'tcx> {
@@ -78,6 +87,87 @@ fn target(&'this self, edge: &OutlivesConstraint) -> RegionVid {
     }
 }
 
+struct BlamedConstraints<'a, 'tcx> {
+    regioncx: &'a RegionInferenceContext<'tcx>,
+    constraints: Vec<OutlivesConstraint>,
+    blamed_paths: Vec<BlamedPath>,
+}
+
+impl BlamedConstraints<'_, '_> {
+    fn on_blamed_path(&self, constraint: &OutlivesConstraint) -> bool {
+        self.blamed_paths.iter().any(|path| path.constraints.contains(constraint))
+    }
+
+    fn is_blamed(&self, constraint: &OutlivesConstraint) -> bool {
+        self.blamed_paths
+            .iter()
+            .any(|path| path.blamed.map(|i| &path.constraints[i]) == Some(constraint))
+    }
+}
+
+impl<'a, 'this, 'tcx> dot::Labeller<'this> for BlamedConstraints<'a, 'tcx> {
+    type Node = RegionVid;
+    type Edge = OutlivesConstraint;
+
+    fn graph_id(&'this self) -> dot::Id<'this> {
+        dot::Id::new("RegionInferenceContext").unwrap()
+    }
+    fn node_id(&'this self, n: &RegionVid) -> dot::Id<'this> {
+        dot::Id::new(format!("r{}", n.index())).unwrap()
+    }
+    fn node_shape(&'this self, _node: &RegionVid) -> Option<dot::LabelText<'this>> {
+        Some(dot::LabelText::LabelStr(Cow::Borrowed("box")))
+    }
+    fn node_label(&'this self, n: &RegionVid) -> dot::LabelText<'this> {
+        match self.regioncx.definitions[*n].external_name {
+            Some(name) => dot::LabelText::LabelStr(format!("{:?} ({:?})", n, name).into()),
+            None => dot::LabelText::LabelStr(format!("{:?}", n).into()),
+        }
+    }
+    fn edge_label(&'this self, e: &OutlivesConstraint) -> dot::LabelText<'this> {
+        let label = format!("{:?}\n{:?}", e.locations, e.category);
+        if self.is_blamed(e) {
+            dot::LabelText::EscStr(format!("blamed: {}", label).into())
+        } else {
+            dot::LabelText::EscStr(label.into())
+        }
+    }
+    fn node_style(&'this self, n: &RegionVid) -> dot::Style {
+        let on_blamed_path = self
+            .blamed_paths
+            .iter()
+            .flat_map(|path| &path.constraints)
+            .any(|constraint| constraint.sup == *n || constraint.sub == *n);
+        if on_blamed_path { dot::Style::Bold } else { dot::Style::None }
+    }
+    fn edge_style(&'this self, e: &OutlivesConstraint) -> dot::Style {
+        if self.on_blamed_path(e) { dot::Style::Bold } else { dot::Style::Dashed }
+    }
+}
+
+impl<'a, 'this, 'tcx> dot::GraphWalk<'this> for BlamedConstraints<'a, 'tcx> {
+    type Node = RegionVid;
+    type Edge = OutlivesConstraint;
+
+    fn nodes(&'this self) -> dot::Nodes<'this, RegionVid> {
+        let vids: Vec<RegionVid> = self.regioncx.definitions.indices().collect();
+        vids.into()
+    }
+    fn edges(&'this self) -> dot::Edges<'this, OutlivesConstraint> {
+        (&self.constraints[..]).into()
+    }
+
+    // Render `a: b` as `a -> b`, like `RawConstraints` does.
+
+    fn source(&'this self, edge: &OutlivesConstraint) -> RegionVid {
+        edge.sup
+    }
+
+    fn target(&'this self, edge: &OutlivesConstraint) -> RegionVid {
+        edge.sub
+    }
+}
+
 struct SccConstraints<'a, 'tcx> {
     regioncx: &'a RegionInferenceContext<'tcx>,
     nodes_per_scc: IndexVec<ConstraintSccIndex, Vec<RegionVid>>,
@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::rc::Rc;
 
@@ -34,6 +35,7 @@
 };
 
 mod dump_mir;
+mod explain;
 mod graphviz;
 mod opaque_types;
 mod reverse_sccs;
@@ -113,6 +115,10 @@ pub struct RegionInferenceContext<'tcx> {
     /// Information about how the universally quantified regions in
     /// scope on this function relate to one another.
     universal_region_relations: Frozen<UniversalRegionRelations<'tcx>>,
+
+    /// The constraint paths blamed by `best_blame_constraint`, for
+    /// `-Z explain-borrowck`. Only recorded when that is enabled.
+    blamed_paths: Option<RefCell<Vec<explain::BlamedPath>>>,
 }
 
 /// Each time that `apply_member_constraint` is successful, it appends
@@ -299,6 +305,7 @@ pub(in crate::borrow_check) fn new(
             type_tests,
             universal_regions,
             universal_region_relations,
+            blamed_paths: None,
         };
 
         result.init_free_and_bound_regions();
@@ -514,6 +521,17 @@ pub fn to_region_vid(&self, r: ty::Region<'tcx>) -> RegionVid {
         self.scc_values.contains(scc, p)
     }
 
+    /// Returns `true` if the region `r` is live at `location`, i.e. if it appears in the type of
+    /// a local that may be used at or after `location`. Universal regions are live everywhere.
+    crate fn is_live_at(&self, r: RegionVid, location: Location) -> bool {
+        self.liveness_constraints.contains(r, location)
+    }
+
+    /// Returns the outlives constraints computed by the type-check.
+    crate fn outlives_constraints(&self) -> impl Iterator<Item = &OutlivesConstraint> + '_ {
+        self.constraints.outlives().iter()
+    }
+
     /// Returns access to the value of `r` for debugging purposes.
     crate fn region_value_str(&self, r: RegionVid) -> String {
         let scc = self.constraint_sccs.scc(r.to_region_vid());
@@ -2101,6 +2119,12 @@ fn check_member_constraints(
             best_choice, blame_source
         );
 
+        if let Some(blamed_paths) = &self.blamed_paths {
+            blamed_paths
+                .borrow_mut()
+                .push(explain::BlamedPath { constraints: path.clone(), blamed: best_choice });
+        }
+
         if let Some(i) = best_choice {
             if let Some(next) = categorized_path.get(i + 1) {
                 if matches!(categorized_path[i].0, ConstraintCategory::Return(_))
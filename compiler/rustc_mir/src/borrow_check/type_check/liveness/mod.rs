@@ -45,7 +45,13 @@ pub(super) fn generate<'mir, 'tcx>(
         &typeck.borrowck_context.universal_regions,
         &typeck.borrowck_context.constraints.outlives_constraints,
     );
-    let live_locals = compute_live_locals(typeck.tcx(), &free_regions, &body);
+    // The location-sensitive computation of where loans are live needs to know where all the
+    // regions are live, including the ones that outlive free regions.
+    let live_locals = if typeck.tcx().sess.opts.debugging_opts.location_sensitive_borrowck {
+        body.local_decls.indices().collect()
+    } else {
+        compute_live_locals(typeck.tcx(), &free_regions, &body)
+    };
     let facts_enabled = AllFacts::enabled(typeck.tcx());
 
     let polonius_drop_used = if facts_enabled {
@@ -6,7 +6,8 @@
 use rustc_index::bit_set::BitSet;
 
 use crate::borrow_check::{
-    places_conflict, BorrowSet, PlaceConflictBias, PlaceExt, RegionInferenceContext, ToRegionVid,
+    places_conflict, BorrowSet, LoanLiveness, PlaceConflictBias, PlaceExt, RegionInferenceContext,
+    ToRegionVid,
 };
 use crate::dataflow::{self, fmt::DebugWithContext, GenKill};
 
@@ -44,16 +45,22 @@ struct OutOfScopePrecomputer<'a, 'tcx> {
     visit_stack: Vec<StackEntry>,
     body: &'a Body<'tcx>,
     regioncx: &'a RegionInferenceContext<'tcx>,
+    loan_liveness: Option<&'a LoanLiveness>,
     borrows_out_of_scope_at_location: FxHashMap<Location, Vec<BorrowIndex>>,
 }
 
 impl<'a, 'tcx> OutOfScopePrecomputer<'a, 'tcx> {
-    fn new(body: &'a Body<'tcx>, regioncx: &'a RegionInferenceContext<'tcx>) -> Self {
+    fn new(
+        body: &'a Body<'tcx>,
+        regioncx: &'a RegionInferenceContext<'tcx>,
+        loan_liveness: Option<&'a LoanLiveness>,
+    ) -> Self {
         OutOfScopePrecomputer {
             visited: BitSet::new_empty(body.basic_blocks().len()),
             visit_stack: vec![],
             body,
             regioncx,
+            loan_liveness,
             borrows_out_of_scope_at_location: FxHashMap::default(),
         }
     }
@@ -90,8 +97,13 @@ fn precompute_borrows_out_of_scope(
             for i in lo..=hi {
                 let location = Location { block: bb, statement_index: i };
                 // If region does not contain a point at the location, then add to list and skip
-                // successor locations.
-                if !self.regioncx.region_contains(borrow_region, location) {
+                // successor locations. With `-Z location-sensitive-borrowck`, the borrow also
+                // goes out of scope where the location-sensitive analysis finds it dead.
+                let in_scope = self.regioncx.region_contains(borrow_region, location)
+                    && self
+                        .loan_liveness
+                        .map_or(true, |liveness| liveness.is_live_at(borrow_index, location));
+                if !in_scope {
                     debug!("borrow {:?} gets killed at {:?}", borrow_index, location);
                     self.borrows_out_of_scope_at_location
                         .entry(location)
@@ -144,9 +156,10 @@ impl<'a, 'tcx> Borrows<'a, 'tcx> {
         tcx: TyCtxt<'tcx>,
         body: &'a Body<'tcx>,
         nonlexical_regioncx: &'a RegionInferenceContext<'tcx>,
+        loan_liveness: Option<&'a LoanLiveness>,
         borrow_set: &'a BorrowSet<'tcx>,
     ) -> Self {
-        let mut prec = OutOfScopePrecomputer::new(body, nonlexical_regioncx);
+        let mut prec = OutOfScopePrecomputer::new(body, nonlexical_regioncx, loan_liveness);
         for (borrow_index, borrow_data) in borrow_set.iter_enumerated() {
             let borrow_region = borrow_data.region.to_region_vid();
             let location = borrow_data.reserve_location;
@@ -0,0 +1,539 @@
+use rustc_index::vec::IndexVec;
+use rustc_middle::mir::visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, ParamEnv, Ty, TyCtxt};
+use rustc_target::abi::Size;
+
+use crate::dataflow::fmt::DebugWithContext;
+use crate::dataflow::framework::SwitchIntEdgeEffects;
+use crate::dataflow::{Analysis, AnalysisDomain, JoinSemiLattice};
+
+/// The largest `blocks * locals` of a body the analysis runs on, see [`KnownLocalBits`].
+const MAX_STATE_ELEMENTS: usize = 1 << 18;
+
+/// What is known about the bits of a scalar value.
+///
+/// This forms a lattice whose bottom is `Bottom` and whose top is a `Known`
+/// value without any known bit. Joining two values keeps the bits that are
+/// known and equal in both, so a loop can only lose knowledge a finite number
+/// of times before reaching a fixpoint.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum KnownBits {
+    /// No value reaches this point: the local is uninitialized, or the code is
+    /// unreachable.
+    Bottom,
+    /// The bits set in `zeros` are known to be zero, and the bits set in `ones`
+    /// are known to be one. All other bits are unknown.
+    Known { zeros: u128, ones: u128 },
+}
+
+impl KnownBits {
+    /// A value about which nothing is known.
+    pub const TOP: KnownBits = KnownBits::Known { zeros: 0, ones: 0 };
+
+    pub fn constant(bits: u128, size: Size) -> KnownBits {
+        KnownBits::Known { zeros: size.truncate(!bits), ones: size.truncate(bits) }
+    }
+
+    /// Returns the value as a constant of the given size, if all its bits are
+    /// known.
+    pub fn as_constant(self, size: Size) -> Option<u128> {
+        match self {
+            KnownBits::Known { zeros, ones } if zeros | ones == size.truncate(u128::MAX) => {
+                Some(ones)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the smallest and largest unsigned values compatible with the
+    /// known bits.
+    fn unsigned_range(self, size: Size) -> Option<(u128, u128)> {
+        match self {
+            KnownBits::Bottom => None,
+            KnownBits::Known { zeros, ones } => Some((ones, size.truncate(!zeros))),
+        }
+    }
+
+    /// Keeps the bits that are known in both `self` and `other`, or returns
+    /// `Bottom` if they contradict each other.
+    fn meet(self, other: KnownBits) -> KnownBits {
+        match (self, other) {
+            (
+                KnownBits::Known { zeros: a_zeros, ones: a_ones },
+                KnownBits::Known { zeros: b_zeros, ones: b_ones },
+            ) => {
+                let (zeros, ones) = (a_zeros | b_zeros, a_ones | b_ones);
+                if zeros & ones != 0 { KnownBits::Bottom } else { KnownBits::Known { zeros, ones } }
+            }
+            _ => KnownBits::Bottom,
+        }
+    }
+}
+
+impl JoinSemiLattice for KnownBits {
+    fn join(&mut self, other: &Self) -> bool {
+        let result = match (*self, *other) {
+            (_, KnownBits::Bottom) => return false,
+            (KnownBits::Bottom, other) => other,
+            (
+                KnownBits::Known { zeros: a_zeros, ones: a_ones },
+                KnownBits::Known { zeros: b_zeros, ones: b_ones },
+            ) => KnownBits::Known { zeros: a_zeros & b_zeros, ones: a_ones & b_ones },
+        };
+        if result == *self {
+            return false;
+        }
+        *self = result;
+        true
+    }
+}
+
+impl<C> DebugWithContext<C> for KnownBits {}
+
+/// A dataflow analysis that tracks which bits of integer, `bool` and `char`
+/// locals are known at each point of a function.
+///
+/// Unlike const propagation through the interpreter, which follows a single
+/// path through the function, this analysis joins the facts of all paths
+/// reaching a block, so a local that has the same value on both arms of an
+/// `if` is still known after it. Along the edges of a `SwitchInt` on a local,
+/// the local is known to be equal to the value of the edge.
+///
+/// Only locals whose address is never taken are tracked, since the others may
+/// be changed through a pointer.
+///
+/// The state of each block holds a `KnownBits` for every local, so the
+/// analysis takes `O(blocks * locals)` space. It isn't run on bodies where
+/// that exceeds `MAX_STATE_ELEMENTS`.
+pub struct KnownLocalBits<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    body: &'a Body<'tcx>,
+    /// The size of each tracked local, or `None` if the local isn't tracked.
+    sizes: IndexVec<Local, Option<Size>>,
+}
+
+impl<'a, 'tcx> KnownLocalBits<'a, 'tcx> {
+    /// Creates the analysis, or returns `None` if `body` is too large for it.
+    pub fn new(tcx: TyCtxt<'tcx>, param_env: ParamEnv<'tcx>, body: &'a Body<'tcx>) -> Option<Self> {
+        let num_blocks = body.basic_blocks().len();
+        let num_locals = body.local_decls.len();
+        if num_blocks.checked_mul(num_locals).map_or(true, |n| n > MAX_STATE_ELEMENTS) {
+            debug!(
+                "KnownLocalBits::new: {} blocks and {} locals are too many",
+                num_blocks, num_locals
+            );
+            return None;
+        }
+
+        let mut analysis = KnownLocalBits { tcx, param_env, body, sizes: IndexVec::new() };
+        analysis.sizes = body
+            .local_decls
+            .iter()
+            .map(|decl| {
+                let ty = decl.ty;
+                if ty.is_integral() || ty.is_bool() || ty.is_char() {
+                    analysis.size_of(ty)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        UntrackBorrowedLocals { sizes: &mut analysis.sizes }.visit_body(body);
+        Some(analysis)
+    }
+
+    fn size_of(&self, ty: Ty<'tcx>) -> Option<Size> {
+        self.tcx.layout_of(self.param_env.and(ty)).ok().map(|layout| layout.size)
+    }
+
+    /// Returns the value of `operand` in `state`, if it is known to be a
+    /// constant.
+    pub fn known_constant(
+        &self,
+        state: &IndexVec<Local, KnownBits>,
+        operand: &Operand<'tcx>,
+    ) -> Option<u128> {
+        let size = self.size_of(operand.ty(self.body, self.tcx))?;
+        self.eval_operand(state, operand).as_constant(size)
+    }
+
+    fn eval_operand(
+        &self,
+        state: &IndexVec<Local, KnownBits>,
+        operand: &Operand<'tcx>,
+    ) -> KnownBits {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => match place.as_local() {
+                Some(local) if self.sizes[local].is_some() => state[local],
+                _ => KnownBits::TOP,
+            },
+            Operand::Constant(constant) => {
+                let ty = constant.literal.ty;
+                if !(ty.is_integral() || ty.is_bool() || ty.is_char()) {
+                    return KnownBits::TOP;
+                }
+                match (
+                    constant.literal.try_eval_bits(self.tcx, self.param_env, ty),
+                    self.size_of(ty),
+                ) {
+                    (Some(bits), Some(size)) => KnownBits::constant(bits, size),
+                    _ => KnownBits::TOP,
+                }
+            }
+        }
+    }
+
+    /// Returns what is known about the value of `rvalue`, which is assigned to
+    /// a tracked local of size `size`.
+    fn eval_rvalue(
+        &self,
+        state: &IndexVec<Local, KnownBits>,
+        rvalue: &Rvalue<'tcx>,
+        size: Size,
+    ) -> KnownBits {
+        match rvalue {
+            Rvalue::Use(operand) => self.eval_operand(state, operand),
+            Rvalue::BinaryOp(op, box (left, right)) => {
+                self.eval_binary_op(state, *op, left, right, size)
+            }
+            Rvalue::UnaryOp(op, operand) => {
+                let ty = operand.ty(self.body, self.tcx);
+                let value = self.eval_operand(state, operand);
+                match (op, value) {
+                    (_, KnownBits::Bottom) => KnownBits::Bottom,
+                    (UnOp::Not, _) if ty.is_bool() => match value.as_constant(size) {
+                        Some(bits) => KnownBits::constant(bits ^ 1, size),
+                        None => KnownBits::TOP,
+                    },
+                    (UnOp::Not, KnownBits::Known { zeros, ones }) => {
+                        KnownBits::Known { zeros: ones, ones: zeros }
+                    }
+                    (UnOp::Neg, _) => match value.as_constant(size) {
+                        Some(bits) => KnownBits::constant(
+                            (size.sign_extend(bits) as i128).wrapping_neg() as u128,
+                            size,
+                        ),
+                        None => KnownBits::TOP,
+                    },
+                }
+            }
+            Rvalue::Cast(CastKind::Misc, operand, _) => {
+                let from_ty = operand.ty(self.body, self.tcx);
+                let from_size = match self.size_of(from_ty) {
+                    Some(from_size)
+                        if from_ty.is_integral() || from_ty.is_bool() || from_ty.is_char() =>
+                    {
+                        from_size
+                    }
+                    _ => return KnownBits::TOP,
+                };
+                match self.eval_operand(state, operand) {
+                    KnownBits::Bottom => KnownBits::Bottom,
+                    value @ KnownBits::Known { zeros, ones } => {
+                        if let Some(bits) = value.as_constant(from_size) {
+                            let bits = if from_ty.is_signed() {
+                                from_size.sign_extend(bits)
+                            } else {
+                                bits
+                            };
+                            KnownBits::constant(bits, size)
+                        } else if size <= from_size {
+                            // Truncation keeps the low bits.
+                            KnownBits::Known {
+                                zeros: size.truncate(zeros),
+                                ones: size.truncate(ones),
+                            }
+                        } else if !from_ty.is_signed() {
+                            // Zero extension sets the high bits to zero.
+                            let high_bits = size.truncate(!from_size.truncate(u128::MAX));
+                            KnownBits::Known { zeros: zeros | high_bits, ones }
+                        } else {
+                            KnownBits::TOP
+                        }
+                    }
+                }
+            }
+            Rvalue::Len(place) => match place.ty(self.body, self.tcx).ty.kind() {
+                ty::Array(_, len) => match len.try_eval_usize(self.tcx, self.param_env) {
+                    Some(len) => KnownBits::constant(len.into(), size),
+                    None => KnownBits::TOP,
+                },
+                _ => KnownBits::TOP,
+            },
+            Rvalue::NullaryOp(NullOp::SizeOf, ty) => match self.size_of(*ty) {
+                Some(ty_size) => KnownBits::constant(ty_size.bytes().into(), size),
+                None => KnownBits::TOP,
+            },
+            Rvalue::Cast(..)
+            | Rvalue::NullaryOp(NullOp::Box, _)
+            | Rvalue::CheckedBinaryOp(..)
+            | Rvalue::Repeat(..)
+            | Rvalue::Ref(..)
+            | Rvalue::ThreadLocalRef(..)
+            | Rvalue::AddressOf(..)
+            | Rvalue::Discriminant(..)
+            | Rvalue::Aggregate(..) => KnownBits::TOP,
+        }
+    }
+
+    fn eval_binary_op(
+        &self,
+        state: &IndexVec<Local, KnownBits>,
+        op: BinOp,
+        left: &Operand<'tcx>,
+        right: &Operand<'tcx>,
+        size: Size,
+    ) -> KnownBits {
+        let left_ty = left.ty(self.body, self.tcx);
+        let right_ty = right.ty(self.body, self.tcx);
+        let (left_size, right_size) = match (self.size_of(left_ty), self.size_of(right_ty)) {
+            (Some(left_size), Some(right_size)) => (left_size, right_size),
+            _ => return KnownBits::TOP,
+        };
+        let (left, right) = match (self.eval_operand(state, left), self.eval_operand(state, right))
+        {
+            (KnownBits::Bottom, _) | (_, KnownBits::Bottom) => return KnownBits::Bottom,
+            values => values,
+        };
+
+        if let (Some(l), Some(r)) = (left.as_constant(left_size), right.as_constant(right_size)) {
+            return match binary_op_bits(op, left_ty, left_size, l, r) {
+                Some((bits, _overflow)) => KnownBits::constant(bits, size),
+                None => KnownBits::TOP,
+            };
+        }
+
+        let (l_zeros, l_ones, r_zeros, r_ones) = match (left, right) {
+            (
+                KnownBits::Known { zeros: l_zeros, ones: l_ones },
+                KnownBits::Known { zeros: r_zeros, ones: r_ones },
+            ) => (l_zeros, l_ones, r_zeros, r_ones),
+            _ => return KnownBits::TOP,
+        };
+        let from_bool = |b: bool| KnownBits::constant(b as u128, size);
+        match op {
+            BinOp::BitAnd => KnownBits::Known { zeros: l_zeros | r_zeros, ones: l_ones & r_ones },
+            BinOp::BitOr => KnownBits::Known { zeros: l_zeros & r_zeros, ones: l_ones | r_ones },
+            BinOp::BitXor => {
+                let known = (l_zeros | l_ones) & (r_zeros | r_ones);
+                let ones = known & (l_ones ^ r_ones);
+                KnownBits::Known { zeros: known & !ones, ones }
+            }
+            // Two values with a bit known to differ are never equal.
+            BinOp::Eq if (l_ones & r_zeros) | (l_zeros & r_ones) != 0 => from_bool(false),
+            BinOp::Ne if (l_ones & r_zeros) | (l_zeros & r_ones) != 0 => from_bool(true),
+            BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge if !left_ty.is_signed() => {
+                let ((l_min, l_max), (r_min, r_max)) =
+                    match (left.unsigned_range(left_size), right.unsigned_range(right_size)) {
+                        (Some(l), Some(r)) => (l, r),
+                        _ => return KnownBits::TOP,
+                    };
+                let known = match op {
+                    BinOp::Lt if l_max < r_min => Some(true),
+                    BinOp::Lt if l_min >= r_max => Some(false),
+                    BinOp::Le if l_max <= r_min => Some(true),
+                    BinOp::Le if l_min > r_max => Some(false),
+                    BinOp::Gt if l_min > r_max => Some(true),
+                    BinOp::Gt if l_max <= r_min => Some(false),
+                    BinOp::Ge if l_min >= r_max => Some(true),
+                    BinOp::Ge if l_max < r_min => Some(false),
+                    _ => None,
+                };
+                known.map_or(KnownBits::TOP, from_bool)
+            }
+            _ => KnownBits::TOP,
+        }
+    }
+}
+
+/// Computes `op` on the constants `l` and `r`, where `l` is of type `ty` and
+/// size `size`. Returns the result and whether the operation overflowed, or
+/// `None` if the result isn't defined.
+fn binary_op_bits(op: BinOp, ty: Ty<'_>, size: Size, l: u128, r: u128) -> Option<(u128, bool)> {
+    let signed = ty.is_signed();
+    if let BinOp::Shl | BinOp::Shr = op {
+        // The shift amount is masked, and the shift overflows if any of the
+        // masked out bits are set.
+        let bits = u128::from(size.bits());
+        let amount = (r % bits) as u32;
+        let result = match op {
+            BinOp::Shl => size.truncate(l << amount),
+            _ if signed => size.truncate((size.sign_extend(l) as i128 >> amount) as u128),
+            _ => l >> amount,
+        };
+        return Some((result, r >= bits));
+    }
+
+    if signed {
+        let (l, r) = (size.sign_extend(l) as i128, size.sign_extend(r) as i128);
+        let (result, overflow) = match op {
+            BinOp::Add => l.overflowing_add(r),
+            BinOp::Sub => l.overflowing_sub(r),
+            BinOp::Mul => l.overflowing_mul(r),
+            BinOp::Div | BinOp::Rem if r == 0 => return None,
+            BinOp::Div => l.overflowing_div(r),
+            BinOp::Rem => l.overflowing_rem(r),
+            BinOp::BitAnd => (l & r, false),
+            BinOp::BitOr => (l | r, false),
+            BinOp::BitXor => (l ^ r, false),
+            _ => return compare(op, l, r),
+        };
+        let truncated = size.truncate(result as u128);
+        Some((truncated, overflow || size.sign_extend(truncated) as i128 != result))
+    } else {
+        let (result, overflow) = match op {
+            BinOp::Add => l.overflowing_add(r),
+            BinOp::Sub => l.overflowing_sub(r),
+            BinOp::Mul => l.overflowing_mul(r),
+            BinOp::Div | BinOp::Rem if r == 0 => return None,
+            BinOp::Div => l.overflowing_div(r),
+            BinOp::Rem => l.overflowing_rem(r),
+            BinOp::BitAnd => (l & r, false),
+            BinOp::BitOr => (l | r, false),
+            BinOp::BitXor => (l ^ r, false),
+            _ => return compare(op, l, r),
+        };
+        let truncated = size.truncate(result);
+        Some((truncated, overflow || truncated != result))
+    }
+}
+
+fn compare<T: Ord>(op: BinOp, l: T, r: T) -> Option<(u128, bool)> {
+    let result = match op {
+        BinOp::Eq => l == r,
+        BinOp::Ne => l != r,
+        BinOp::Lt => l < r,
+        BinOp::Le => l <= r,
+        BinOp::Gt => l > r,
+        BinOp::Ge => l >= r,
+        _ => return None,
+    };
+    Some((result as u128, false))
+}
+
+impl<'tcx> AnalysisDomain<'tcx> for KnownLocalBits<'_, 'tcx> {
+    type Domain = IndexVec<Local, KnownBits>;
+
+    const NAME: &'static str = "known_local_bits";
+
+    fn bottom_value(&self, body: &Body<'tcx>) -> Self::Domain {
+        IndexVec::from_elem(KnownBits::Bottom, &body.local_decls)
+    }
+
+    fn initialize_start_block(&self, body: &Body<'tcx>, state: &mut Self::Domain) {
+        for arg in body.args_iter() {
+            state[arg] = KnownBits::TOP;
+        }
+    }
+}
+
+impl<'tcx> Analysis<'tcx> for KnownLocalBits<'_, 'tcx> {
+    fn apply_statement_effect(
+        &self,
+        state: &mut Self::Domain,
+        statement: &Statement<'tcx>,
+        location: Location,
+    ) {
+        match &statement.kind {
+            StatementKind::Assign(box (place, rvalue)) => {
+                if let Some(local) = place.as_local() {
+                    if let Some(size) = self.sizes[local] {
+                        state[local] = self.eval_rvalue(state, rvalue, size);
+                    }
+                }
+            }
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
+                state[*local] = KnownBits::Bottom;
+            }
+            _ => ForgetMutatedLocals { state }.visit_statement(statement, location),
+        }
+    }
+
+    fn apply_terminator_effect(
+        &self,
+        state: &mut Self::Domain,
+        terminator: &Terminator<'tcx>,
+        location: Location,
+    ) {
+        ForgetMutatedLocals { state }.visit_terminator(terminator, location);
+    }
+
+    fn apply_call_return_effect(
+        &self,
+        state: &mut Self::Domain,
+        _block: BasicBlock,
+        _func: &Operand<'tcx>,
+        _args: &[Operand<'tcx>],
+        return_place: Place<'tcx>,
+    ) {
+        if let Some(local) = return_place.as_local() {
+            state[local] = KnownBits::TOP;
+        }
+    }
+
+    fn apply_yield_resume_effect(
+        &self,
+        state: &mut Self::Domain,
+        _resume_block: BasicBlock,
+        resume_place: Place<'tcx>,
+    ) {
+        if let Some(local) = resume_place.as_local() {
+            state[local] = KnownBits::TOP;
+        }
+    }
+
+    fn apply_switch_int_edge_effects(
+        &self,
+        _block: BasicBlock,
+        discr: &Operand<'tcx>,
+        edge_effects: &mut impl SwitchIntEdgeEffects<Self::Domain>,
+    ) {
+        let (local, size) = match discr.place().and_then(|place| place.as_local()) {
+            Some(local) => match self.sizes[local] {
+                Some(size) => (local, size),
+                None => return,
+            },
+            None => return,
+        };
+        edge_effects.apply(|state, edge| {
+            if let Some(value) = edge.value {
+                state[local] = state[local].meet(KnownBits::constant(value, size));
+            }
+        });
+    }
+}
+
+/// Stops tracking the locals whose address is taken.
+struct UntrackBorrowedLocals<'a> {
+    sizes: &'a mut IndexVec<Local, Option<Size>>,
+}
+
+impl<'tcx> Visitor<'tcx> for UntrackBorrowedLocals<'_> {
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, _: Location) {
+        if context.is_borrow()
+            || matches!(
+                context,
+                PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf)
+                    | PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+            )
+        {
+            self.sizes[local] = None;
+        }
+    }
+}
+
+/// Forgets what is known about the locals that are changed in other ways than
+/// by an assignment, like by a call or by inline assembly.
+struct ForgetMutatedLocals<'a> {
+    state: &'a mut IndexVec<Local, KnownBits>,
+}
+
+impl<'tcx> Visitor<'tcx> for ForgetMutatedLocals<'_> {
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, _: Location) {
+        if context.is_mutating_use() {
+            self.state[local] = KnownBits::TOP;
+        }
+    }
+}
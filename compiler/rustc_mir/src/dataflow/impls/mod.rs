@@ -23,12 +23,14 @@
 mod borrowed_locals;
 pub(super) mod borrows;
 mod init_locals;
+mod known_bits;
 mod liveness;
 mod storage_liveness;
 
 pub use self::borrowed_locals::{MaybeBorrowedLocals, MaybeMutBorrowedLocals};
 pub use self::borrows::Borrows;
 pub use self::init_locals::MaybeInitializedLocals;
+pub use self::known_bits::{KnownBits, KnownLocalBits};
 pub use self::liveness::MaybeLiveLocals;
 pub use self::storage_liveness::{MaybeRequiresStorage, MaybeStorageLive};
 
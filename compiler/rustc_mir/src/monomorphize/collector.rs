@@ -263,6 +263,11 @@ pub fn with_inlining_candidates<F>(&self, source: MonoItem<'tcx>, mut f: F)
         }
     }
 
+    // Returns all the items referenced by `source`.
+    pub fn accesses(&self, source: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
+        self.index.get(&source).map_or(&[][..], |range| &self.targets[range.clone()])
+    }
+
     // Internally iterate over all items and the things each accesses.
     pub fn iter_accesses<F>(&self, mut f: F)
     where
@@ -7,6 +7,7 @@
 pub mod collector;
 pub mod partitioning;
 pub mod polymorphize;
+pub mod stack_usage;
 
 pub fn custom_coerce_unsize_info<'tcx>(
     tcx: TyCtxt<'tcx>,
@@ -95,18 +95,24 @@
 mod default;
 mod merging;
 
+use std::collections::BTreeMap;
+use std::fs;
+
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync;
-use rustc_hir::def_id::{CrateNum, DefIdSet, LOCAL_CRATE};
+use rustc_hir::def_id::{CrateNum, DefId, DefIdSet, LOCAL_CRATE};
 use rustc_middle::mir::mono::MonoItem;
 use rustc_middle::mir::mono::{CodegenUnit, Linkage};
 use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::query::Providers;
+use rustc_middle::ty::subst::GenericArgKind;
 use rustc_middle::ty::TyCtxt;
+use rustc_serialize::json::{Json, ToJson};
 use rustc_span::symbol::Symbol;
 
 use crate::monomorphize::collector::InliningMap;
 use crate::monomorphize::collector::{self, MonoItemCollectionMode};
+use crate::monomorphize::stack_usage;
 
 pub struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -347,6 +353,8 @@ fn collect_and_partition_mono_items<'tcx>(
 
     tcx.sess.abort_if_errors();
 
+    stack_usage::check_stack_usage(tcx, &items, &inlining_map);
+
     let (codegen_units, _) = tcx.sess.time("partition_and_assert_distinct_symbols", || {
         sync::join(
             || {
@@ -392,22 +400,8 @@ fn collect_and_partition_mono_items<'tcx>(
                     output.push(' ');
                     output.push_str(&cgu_name.as_str());
 
-                    let linkage_abbrev = match linkage {
-                        Linkage::External => "External",
-                        Linkage::AvailableExternally => "Available",
-                        Linkage::LinkOnceAny => "OnceAny",
-                        Linkage::LinkOnceODR => "OnceODR",
-                        Linkage::WeakAny => "WeakAny",
-                        Linkage::WeakODR => "WeakODR",
-                        Linkage::Appending => "Appending",
-                        Linkage::Internal => "Internal",
-                        Linkage::Private => "Private",
-                        Linkage::ExternalWeak => "ExternalWeak",
-                        Linkage::Common => "Common",
-                    };
-
                     output.push('[');
-                    output.push_str(linkage_abbrev);
+                    output.push_str(linkage_abbrev(linkage));
                     output.push(']');
                 }
                 output
@@ -421,9 +415,127 @@ fn collect_and_partition_mono_items<'tcx>(
         }
     }
 
+    if tcx.sess.opts.debugging_opts.mono_items_report {
+        write_mono_items_report(tcx, &items, codegen_units);
+    }
+
     (tcx.arena.alloc(mono_items), codegen_units)
 }
 
+fn linkage_abbrev(linkage: Linkage) -> &'static str {
+    match linkage {
+        Linkage::External => "External",
+        Linkage::AvailableExternally => "Available",
+        Linkage::LinkOnceAny => "OnceAny",
+        Linkage::LinkOnceODR => "OnceODR",
+        Linkage::WeakAny => "WeakAny",
+        Linkage::WeakODR => "WeakODR",
+        Linkage::Appending => "Appending",
+        Linkage::Internal => "Internal",
+        Linkage::Private => "Private",
+        Linkage::ExternalWeak => "ExternalWeak",
+        Linkage::Common => "Common",
+    }
+}
+
+/// Writes the instantiations of each generic function for `-Z mono-items-report`,
+/// grouped by the definition they were instantiated from, the definitions with
+/// the most MIR statements in total first.
+fn write_mono_items_report<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    codegen_units: &[CodegenUnit<'tcx>],
+) {
+    let mut item_to_cgus: FxHashMap<_, Vec<_>> = Default::default();
+    for cgu in codegen_units {
+        for (&mono_item, &(linkage, _)) in cgu.items() {
+            item_to_cgus.entry(mono_item).or_default().push((cgu.name(), linkage));
+        }
+    }
+
+    let mut instances_per_def: FxHashMap<DefId, Vec<(Vec<String>, usize, Json)>> =
+        Default::default();
+    for item in items {
+        let instance = match *item {
+            MonoItem::Fn(instance) if item.is_generic_fn() => instance,
+            _ => continue,
+        };
+
+        let type_arguments: Vec<String> = with_no_trimmed_paths(|| {
+            instance
+                .substs
+                .iter()
+                .filter(|arg| !matches!(arg.unpack(), GenericArgKind::Lifetime(_)))
+                .map(|arg| arg.to_string())
+                .collect()
+        });
+        let statements: usize = tcx
+            .instance_mir(instance.def)
+            .basic_blocks()
+            .iter()
+            .map(|block| block.statements.len())
+            .sum();
+
+        let mut cgus = item_to_cgus.get(item).cloned().unwrap_or_default();
+        cgus.sort_by_key(|&(name, _)| name.as_str());
+        cgus.dedup();
+        let cgus = cgus
+            .into_iter()
+            .map(|(name, linkage)| {
+                let mut object = BTreeMap::new();
+                object.insert("name".to_string(), name.to_string().to_json());
+                object.insert("linkage".to_string(), linkage_abbrev(linkage).to_json());
+                Json::Object(object)
+            })
+            .collect();
+
+        instances_per_def.entry(instance.def_id()).or_default().push((
+            type_arguments,
+            statements,
+            Json::Array(cgus),
+        ));
+    }
+
+    let mut definitions: Vec<_> = instances_per_def
+        .into_iter()
+        .map(|(def_id, mut instances)| {
+            let name = with_no_trimmed_paths(|| tcx.def_path_str(def_id));
+            let total_statements: usize =
+                instances.iter().map(|&(_, statements, _)| statements).sum();
+            instances.sort_by(|a, b| a.0.cmp(&b.0));
+            (name, total_statements, instances)
+        })
+        .collect();
+    definitions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let definitions = definitions
+        .into_iter()
+        .map(|(name, total_statements, instances)| {
+            let mut object = BTreeMap::new();
+            object.insert("definition".to_string(), name.to_json());
+            object.insert("instantiations".to_string(), instances.len().to_json());
+            object.insert("total_statements".to_string(), total_statements.to_json());
+            let instances = instances
+                .into_iter()
+                .map(|(type_arguments, statements, cgus)| {
+                    let mut object = BTreeMap::new();
+                    object.insert("type_arguments".to_string(), type_arguments.to_json());
+                    object.insert("statements".to_string(), statements.to_json());
+                    object.insert("codegen_units".to_string(), cgus);
+                    Json::Object(object)
+                })
+                .collect();
+            object.insert("instances".to_string(), Json::Array(instances));
+            Json::Object(object)
+        })
+        .collect();
+
+    let path = tcx.output_filenames(LOCAL_CRATE).with_extension("mono_items.json");
+    if let Err(e) = fs::write(&path, Json::Array(definitions).pretty().to_string()) {
+        tcx.sess.err(&format!("failed to write {}: {}", path.display(), e));
+    }
+}
+
 pub fn provide(providers: &mut Providers) {
     providers.collect_and_partition_mono_items = collect_and_partition_mono_items;
 
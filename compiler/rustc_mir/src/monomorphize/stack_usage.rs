@@ -0,0 +1,200 @@
+//! Estimates the stack usage of each function from its MIR, for
+//! `-Z stack-usage-report` and the `large_stack_usage` lint.
+//!
+//! The frame of a function is estimated as the sum of the sizes of all its
+//! locals, which is an upper bound as long as LLVM doesn't spill more than it
+//! saves by reusing stack slots. The stack usage of a function is its frame
+//! plus the largest stack usage of the functions it references, as recorded in
+//! the `InliningMap`. Referencing a function, e.g. to build a vtable, doesn't
+//! mean calling it, so this can only overestimate. Functions that aren't
+//! codegened in this crate are not accounted for, and the stack usage of
+//! functions that may recurse is unbounded.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::stack::ensure_sufficient_stack;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, TyCtxt};
+use rustc_serialize::json::{Json, ToJson};
+use rustc_session::lint::builtin::LARGE_STACK_USAGE;
+
+use crate::monomorphize::collector::InliningMap;
+
+#[derive(Copy, Clone, Debug)]
+struct StackUsage<'tcx> {
+    /// The size of the locals of the function.
+    frame: u64,
+
+    /// The frame plus the stack usage of the deepest callee, or `None` if the
+    /// function may recurse.
+    total: Option<u64>,
+
+    /// The callee the deepest call chain goes through.
+    deepest_callee: Option<Instance<'tcx>>,
+}
+
+struct StackUsageCx<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    inlining_map: &'a InliningMap<'tcx>,
+
+    /// The stack usage of the functions visited so far, or `None` for the
+    /// functions whose callees are still being visited.
+    usages: FxHashMap<Instance<'tcx>, Option<StackUsage<'tcx>>>,
+}
+
+impl<'a, 'tcx> StackUsageCx<'a, 'tcx> {
+    fn frame_size(&self, instance: Instance<'tcx>) -> u64 {
+        let param_env = ty::ParamEnv::reveal_all();
+        let body = self.tcx.instance_mir(instance.def);
+        body.local_decls
+            .iter()
+            .map(|decl| {
+                let ty =
+                    instance.subst_mir_and_normalize_erasing_regions(self.tcx, param_env, decl.ty);
+                self.tcx.layout_of(param_env.and(ty)).map_or(0, |layout| layout.size.bytes())
+            })
+            .sum()
+    }
+
+    fn stack_usage(&mut self, instance: Instance<'tcx>) -> StackUsage<'tcx> {
+        match self.usages.get(&instance) {
+            Some(Some(usage)) => return *usage,
+            // We came back to a function whose callees are being visited, so
+            // all the functions in between may recurse.
+            Some(None) => return StackUsage { frame: 0, total: None, deepest_callee: None },
+            None => {}
+        }
+        self.usages.insert(instance, None);
+
+        let frame = self.frame_size(instance);
+        let mut usage = StackUsage { frame, total: Some(frame), deepest_callee: None };
+        let inlining_map = self.inlining_map;
+        for &item in inlining_map.accesses(MonoItem::Fn(instance)) {
+            let callee = match item {
+                MonoItem::Fn(callee) => callee,
+                MonoItem::Static(_) | MonoItem::GlobalAsm(_) => continue,
+            };
+            let callee_total = ensure_sufficient_stack(|| self.stack_usage(callee)).total;
+            usage.total = match (usage.total, callee_total) {
+                (None, _) => continue,
+                (Some(total), Some(callee_total)) if frame + callee_total <= total => continue,
+                (Some(_), Some(callee_total)) => Some(frame + callee_total),
+                (Some(_), None) => None,
+            };
+            usage.deepest_callee = Some(callee);
+        }
+
+        self.usages.insert(instance, Some(usage));
+        usage
+    }
+
+    /// Returns the deepest call chain starting at `instance`, stopping before
+    /// the first function that is already in the chain.
+    fn call_chain(&self, instance: Instance<'tcx>) -> Vec<Instance<'tcx>> {
+        let mut chain = vec![instance];
+        while let Some(&Some(usage)) = self.usages.get(chain.last().unwrap()) {
+            match usage.deepest_callee {
+                Some(callee) if !chain.contains(&callee) => chain.push(callee),
+                _ => break,
+            }
+        }
+        chain
+    }
+}
+
+/// Estimates the stack usage of all the functions in `items`, then lints those
+/// over `-Z stack-usage-limit` and writes the estimates to a JSON file for
+/// `-Z stack-usage-report`.
+pub fn check_stack_usage<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    inlining_map: &InliningMap<'tcx>,
+) {
+    let limit = tcx.sess.opts.debugging_opts.stack_usage_limit;
+    let report = tcx.sess.opts.debugging_opts.stack_usage_report;
+    if limit.is_none() && !report {
+        return;
+    }
+
+    let mut cx = StackUsageCx { tcx, inlining_map, usages: FxHashMap::default() };
+    let mut usages: Vec<_> = items
+        .iter()
+        .filter_map(|item| match *item {
+            MonoItem::Fn(instance) => {
+                let name = with_no_trimmed_paths(|| instance.to_string());
+                Some((instance, name, cx.stack_usage(instance)))
+            }
+            MonoItem::Static(_) | MonoItem::GlobalAsm(_) => None,
+        })
+        .collect();
+    // Deepest first, with the functions that may recurse last.
+    usages.sort_by(|(_, a_name, a), (_, b_name, b)| {
+        (Reverse(a.total), a_name).cmp(&(Reverse(b.total), b_name))
+    });
+
+    let chain_names = |instance| -> Vec<String> {
+        let chain = cx.call_chain(instance);
+        with_no_trimmed_paths(|| chain.iter().map(|instance| instance.to_string()).collect())
+    };
+
+    if let Some(limit) = limit {
+        // Only lint each definition once, for its deepest instance.
+        let mut linted = FxHashSet::default();
+        for &(instance, ref name, usage) in &usages {
+            let total = match usage.total {
+                Some(total) if total > limit as u64 => total,
+                _ => continue,
+            };
+            let def_id = match instance.def {
+                ty::InstanceDef::Item(def) => match def.did.as_local() {
+                    Some(def_id) => def_id,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if !linted.insert(def_id) {
+                continue;
+            }
+
+            let hir_id = tcx.hir().local_def_id_to_hir_id(def_id);
+            tcx.struct_span_lint_hir(LARGE_STACK_USAGE, hir_id, tcx.def_span(def_id), |lint| {
+                let mut diag =
+                    lint.build(&format!("`{}` may use up to {} bytes of stack", name, total));
+                diag.note(&format!(
+                    "its own frame uses {} bytes, the limit is {} bytes",
+                    usage.frame, limit
+                ));
+                let chain = chain_names(instance);
+                if chain.len() > 1 {
+                    let chain: Vec<_> = chain.iter().map(|name| format!("`{}`", name)).collect();
+                    diag.note(&format!("the deepest call chain is {}", chain.join(" -> ")));
+                }
+                diag.emit();
+            });
+        }
+    }
+
+    if report {
+        let functions = usages
+            .iter()
+            .map(|&(instance, ref name, usage)| {
+                let mut object = BTreeMap::new();
+                object.insert("function".to_string(), name.to_json());
+                object.insert("frame_size".to_string(), usage.frame.to_json());
+                object.insert("stack_usage".to_string(), usage.total.to_json());
+                object.insert("call_chain".to_string(), chain_names(instance).to_json());
+                Json::Object(object)
+            })
+            .collect();
+
+        let path = tcx.output_filenames(LOCAL_CRATE).with_extension("stack_usage.json");
+        if let Err(e) = fs::write(&path, Json::Array(functions).pretty().to_string()) {
+            tcx.sess.err(&format!("failed to write {}: {}", path.display(), e));
+        }
+    }
+}
@@ -29,6 +29,8 @@
 use rustc_trait_selection::traits;
 
 use crate::const_eval::ConstEvalErr;
+use crate::dataflow::impls::KnownLocalBits;
+use crate::dataflow::{Analysis, ResultsCursor};
 use crate::interpret::{
     self, compile_time_machine, AllocId, Allocation, ConstValue, CtfeValidationMode, Frame, ImmTy,
     Immediate, InterpCx, InterpResult, LocalState, LocalValue, MemPlace, Memory, MemoryKind, OpTy,
@@ -330,6 +332,11 @@ struct ConstPropagator<'mir, 'tcx> {
     // Because we have `MutVisitor` we can't obtain the `SourceInfo` from a `Location`. So we store
     // the last known `SourceInfo` here and just keep revisiting it.
     source_info: Option<SourceInfo>,
+    // The interpreter only follows the blocks in order and forgets most locals at the end of a
+    // block, so this is consulted for the values of scalar locals it doesn't know. The analysis
+    // joins what is known on all paths reaching a point, so it sees through branches and loops.
+    // It is `None` for bodies that are too large for the analysis.
+    known_bits: Option<ResultsCursor<'mir, 'tcx, KnownLocalBits<'mir, 'tcx>>>,
 }
 
 impl<'mir, 'tcx> LayoutOf for ConstPropagator<'mir, 'tcx> {
@@ -402,6 +409,10 @@ fn new(
         )
         .expect("failed to push initial stack frame");
 
+        let known_bits = KnownLocalBits::new(tcx, param_env, dummy_body).map(|analysis| {
+            analysis.into_engine(tcx, dummy_body).iterate_to_fixpoint().into_results_cursor(dummy_body)
+        });
+
         ConstPropagator {
             ecx,
             tcx,
@@ -412,6 +423,7 @@ fn new(
             //FIXME(wesleywiser) we can't steal this because `Visitor::super_visit_body()` needs it
             local_decls: body.local_decls.clone(),
             source_info: None,
+            known_bits,
         }
     }
 
@@ -525,6 +537,36 @@ fn eval_operand(&mut self, op: &Operand<'tcx>, source_info: SourceInfo) -> Optio
         }
     }
 
+    /// Returns the value of the scalar `op` before `location`, if the `KnownLocalBits` analysis
+    /// knows it.
+    fn known_immediate(&mut self, op: &Operand<'tcx>, location: Location) -> Option<ImmTy<'tcx>> {
+        let known_bits = self.known_bits.as_mut()?;
+        known_bits.seek_before_primary_effect(location);
+        let bits = known_bits.analysis().known_constant(known_bits.get(), op)?;
+        let layout = self.ecx.layout_of(op.ty(&self.local_decls, self.tcx)).ok()?;
+        Some(ImmTy::from_uint(bits, layout))
+    }
+
+    /// Returns the value of the scalar `op` before `location`, and whether it is only known to
+    /// the `KnownLocalBits` analysis.
+    fn read_immediate_or_known(
+        &mut self,
+        op: &Operand<'tcx>,
+        location: Location,
+    ) -> Option<(ImmTy<'tcx>, bool)> {
+        match self.use_ecx(|this| this.ecx.read_immediate(&this.ecx.eval_operand(op, None)?)) {
+            Some(val) => Some((val, false)),
+            None => self.known_immediate(op, location).map(|val| (val, true)),
+        }
+    }
+
+    /// Findings relying on values only known to the `KnownLocalBits` analysis are reported as
+    /// `PANIC_OR_OVERFLOW_ACROSS_BRANCHES` rather than as the deny-by-default `lint`, so that
+    /// code which compiled before the analysis existed keeps compiling.
+    fn assert_lint(lint: &'static lint::Lint, uses_known_bits: bool) -> &'static lint::Lint {
+        if uses_known_bits { lint::builtin::PANIC_OR_OVERFLOW_ACROSS_BRANCHES } else { lint }
+    }
+
     fn report_assert_as_lint(
         &self,
         lint: &'static lint::Lint,
@@ -546,17 +588,18 @@ fn check_unary_op(
         op: UnOp,
         arg: &Operand<'tcx>,
         source_info: SourceInfo,
+        location: Location,
     ) -> Option<()> {
-        if let (val, true) = self.use_ecx(|this| {
-            let val = this.ecx.read_immediate(&this.ecx.eval_operand(arg, None)?)?;
+        let (val, uses_known_bits) = self.read_immediate_or_known(arg, location)?;
+        if self.use_ecx(|this| {
             let (_res, overflow, _ty) = this.ecx.overflowing_unary_op(op, &val)?;
-            Ok((val, overflow))
+            Ok(overflow)
         })? {
             // `AssertKind` only has an `OverflowNeg` variant, so make sure that is
             // appropriate to use.
             assert_eq!(op, UnOp::Neg, "Neg is the only UnOp that can overflow");
             self.report_assert_as_lint(
-                lint::builtin::ARITHMETIC_OVERFLOW,
+                Self::assert_lint(lint::builtin::ARITHMETIC_OVERFLOW, uses_known_bits),
                 source_info,
                 "this arithmetic operation will overflow",
                 AssertKind::OverflowNeg(val.to_const_int()),
@@ -572,12 +615,13 @@ fn check_binary_op(
         left: &Operand<'tcx>,
         right: &Operand<'tcx>,
         source_info: SourceInfo,
+        location: Location,
     ) -> Option<()> {
-        let r = self.use_ecx(|this| this.ecx.read_immediate(&this.ecx.eval_operand(right, None)?));
-        let l = self.use_ecx(|this| this.ecx.read_immediate(&this.ecx.eval_operand(left, None)?));
+        let r = self.read_immediate_or_known(right, location);
+        let l = self.read_immediate_or_known(left, location);
         // Check for exceeding shifts *even if* we cannot evaluate the LHS.
         if op == BinOp::Shr || op == BinOp::Shl {
-            let r = r?;
+            let (r, r_uses_known_bits) = r?;
             // We need the type of the LHS. We cannot use `place_layout` as that is the type
             // of the result, which for checked binops is not the same!
             let left_ty = left.ty(&self.local_decls, self.tcx);
@@ -589,13 +633,13 @@ fn check_binary_op(
             if r_bits.map_or(false, |b| b >= left_size.bits() as u128) {
                 debug!("check_binary_op: reporting assert for {:?}", source_info);
                 self.report_assert_as_lint(
-                    lint::builtin::ARITHMETIC_OVERFLOW,
+                    Self::assert_lint(lint::builtin::ARITHMETIC_OVERFLOW, r_uses_known_bits),
                     source_info,
                     "this arithmetic operation will overflow",
                     AssertKind::Overflow(
                         op,
                         match l {
-                            Some(l) => l.to_const_int(),
+                            Some((l, _)) => l.to_const_int(),
                             // Invent a dummy value, the diagnostic ignores it anyway
                             None => ConstInt::new(
                                 ScalarInt::try_from_uint(1_u8, left_size).unwrap(),
@@ -609,14 +653,15 @@ fn check_binary_op(
             }
         }
 
-        if let (Some(l), Some(r)) = (&l, &r) {
+        if let (Some((l, l_uses_known_bits)), Some((r, r_uses_known_bits))) = (&l, &r) {
             // The remaining operators are handled through `overflowing_binary_op`.
             if self.use_ecx(|this| {
                 let (_res, overflow, _ty) = this.ecx.overflowing_binary_op(op, l, r)?;
                 Ok(overflow)
             })? {
+                let uses_known_bits = *l_uses_known_bits || *r_uses_known_bits;
                 self.report_assert_as_lint(
-                    lint::builtin::ARITHMETIC_OVERFLOW,
+                    Self::assert_lint(lint::builtin::ARITHMETIC_OVERFLOW, uses_known_bits),
                     source_info,
                     "this arithmetic operation will overflow",
                     AssertKind::Overflow(op, l.to_const_int(), r.to_const_int()),
@@ -659,6 +704,7 @@ fn const_prop(
         rvalue: &Rvalue<'tcx>,
         source_info: SourceInfo,
         place: Place<'tcx>,
+        location: Location,
     ) -> Option<()> {
         // Perform any special handling for specific Rvalue types.
         // Generally, checks here fall into one of two categories:
@@ -674,11 +720,11 @@ fn const_prop(
             // lint.
             Rvalue::UnaryOp(op, arg) => {
                 trace!("checking UnaryOp(op = {:?}, arg = {:?})", op, arg);
-                self.check_unary_op(*op, arg, source_info)?;
+                self.check_unary_op(*op, arg, source_info, location)?;
             }
             Rvalue::BinaryOp(op, box (left, right)) => {
                 trace!("checking BinaryOp(op = {:?}, left = {:?}, right = {:?})", op, left, right);
-                self.check_binary_op(*op, left, right, source_info)?;
+                self.check_binary_op(*op, left, right, source_info, location)?;
             }
             Rvalue::CheckedBinaryOp(op, box (left, right)) => {
                 trace!(
@@ -687,7 +733,7 @@ fn const_prop(
                     left,
                     right
                 );
-                self.check_binary_op(*op, left, right, source_info)?;
+                self.check_binary_op(*op, left, right, source_info, location)?;
             }
 
             // Do not try creating references (#67862)
@@ -902,6 +948,14 @@ fn replace_with_const(
         }
     }
 
+    /// Returns `true` if the value of `op` known by the `KnownLocalBits` analysis should be
+    /// const-propagated into it. This changes the branches taken by switches, so it is left to
+    /// optimized builds.
+    fn should_const_prop_known(&self, op: &Operand<'tcx>) -> bool {
+        self.tcx.sess.mir_opt_level() >= 2
+            && self.tcx.consider_optimizing(|| format!("ConstantPropagation - known: {:?}", op))
+    }
+
     /// Returns `true` if and only if this `op` should be const-propagated into.
     fn should_const_prop(&mut self, op: &OpTy<'tcx>) -> bool {
         let mir_opt_level = self.tcx.sess.mir_opt_level();
@@ -1091,7 +1145,7 @@ fn visit_statement(&mut self, statement: &mut Statement<'tcx>, location: Locatio
         self.source_info = Some(source_info);
         if let StatementKind::Assign(box (place, ref mut rval)) = statement.kind {
             let can_const_prop = self.ecx.machine.can_const_prop[place.local];
-            if let Some(()) = self.const_prop(rval, source_info, place) {
+            if let Some(()) = self.const_prop(rval, source_info, place, location) {
                 // This will return None if the above `const_prop` invocation only "wrote" a
                 // type whose creation requires no write. E.g. a generator whose initial state
                 // consists solely of uninitialized memory (so it doesn't capture any locals).
@@ -1178,10 +1232,16 @@ fn visit_terminator(&mut self, terminator: &mut Terminator<'tcx>, location: Loca
         self.super_terminator(terminator, location);
         match &mut terminator.kind {
             TerminatorKind::Assert { expected, ref msg, ref mut cond, .. } => {
-                if let Some(ref value) = self.eval_operand(&cond, source_info) {
-                    trace!("assertion on {:?} should be {:?}", value, expected);
+                let value = self.eval_operand(&cond, source_info);
+                let value_const = match value {
+                    Some(ref value) => Some(self.ecx.read_scalar(&value).unwrap()),
+                    None => self
+                        .known_immediate(cond, location)
+                        .map(|known| known.to_scalar_or_uninit()),
+                };
+                if let Some(value_const) = value_const {
+                    trace!("assertion on {:?} should be {:?}", value_const, expected);
                     let expected = ScalarMaybeUninit::from(Scalar::from_bool(*expected));
-                    let value_const = self.ecx.read_scalar(&value).unwrap();
                     if expected != value_const {
                         enum DbgVal<T> {
                             Val(T),
@@ -1202,7 +1262,10 @@ fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                                 Some(op) => DbgVal::Val(
                                     self.ecx.read_immediate(&op).unwrap().to_const_int(),
                                 ),
-                                None => DbgVal::Underscore,
+                                None => match self.known_immediate(op, location) {
+                                    Some(known) => DbgVal::Val(known.to_const_int()),
+                                    None => DbgVal::Underscore,
+                                },
                             }
                         };
                         let msg = match msg {
@@ -1231,15 +1294,24 @@ fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                             Operand::Constant(_) => {}
                         }
                         if let Some(msg) = msg {
+                            // The message may show values only known to the analysis, but the
+                            // finding itself only relies on the condition.
                             self.report_assert_as_lint(
-                                lint::builtin::UNCONDITIONAL_PANIC,
+                                Self::assert_lint(
+                                    lint::builtin::UNCONDITIONAL_PANIC,
+                                    value.is_none(),
+                                ),
                                 source_info,
                                 "this operation will panic at runtime",
                                 msg,
                             );
                         }
                     } else {
-                        if self.should_const_prop(value) {
+                        let should_const_prop = match value {
+                            Some(ref value) => self.should_const_prop(value),
+                            None => self.should_const_prop_known(cond),
+                        };
+                        if should_const_prop {
                             if let ScalarMaybeUninit::Scalar(scalar) = value_const {
                                 *cond = self.operand_from_scalar(
                                     scalar,
@@ -1251,11 +1323,23 @@ fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                     }
                 }
             }
-            TerminatorKind::SwitchInt { ref mut discr, .. } => {
+            TerminatorKind::SwitchInt { ref mut discr, switch_ty, .. } => {
                 // FIXME: This is currently redundant with `visit_operand`, but sadly
                 // always visiting operands currently causes a perf regression in LLVM codegen, so
                 // `visit_operand` currently only runs for propagates places for `mir_opt_level=4`.
-                self.propagate_operand(discr)
+                self.propagate_operand(discr);
+
+                // Values known on all paths to the switch let `SimplifyBranches` remove it.
+                if !matches!(discr, Operand::Constant(_)) {
+                    if let Some(known) = self.known_immediate(discr, location) {
+                        if self.should_const_prop_known(discr) {
+                            if let ScalarMaybeUninit::Scalar(scalar) = known.to_scalar_or_uninit() {
+                                *discr =
+                                    self.operand_from_scalar(scalar, *switch_ty, source_info.span);
+                            }
+                        }
+                    }
+                }
             }
             // None of these have Operands to const-propagate.
             TerminatorKind::Goto { .. }
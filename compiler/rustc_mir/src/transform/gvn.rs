@@ -0,0 +1,566 @@
+//! See the docs for [`GlobalValueNumbering`].
+
+use crate::transform::MirPass;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::graph::dominators::Dominators;
+use rustc_hir::Mutability;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+use rustc_middle::mir::visit::{
+    MutVisitor, MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor,
+};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, ParamEnv, Ty, TyCtxt};
+use rustc_span::{DUMMY_SP, Span};
+
+/// Replaces computations whose result is already available in a local by a
+/// copy of that local.
+///
+/// The pass assigns a value number to each `Rvalue` that only depends on
+/// locals that are assigned exactly once before all their uses ("SSA locals"),
+/// so that two `Rvalue`s computing the same thing get the same value number.
+/// When the result of an `Rvalue` was already stored into an SSA local whose
+/// assignment dominates the `Rvalue`, the `Rvalue` is replaced by a copy of
+/// that local:
+///
+/// ```text
+/// _3 = Len((*_1));        _3 = Len((*_1));
+/// _4 = Lt(_2, _3);        _4 = Lt(_2, _3);
+/// ...                 =>  ...
+/// _6 = Len((*_1));        _6 = _3;
+/// _7 = Lt(_2, _6);        _7 = _4;
+/// ```
+///
+/// Pure unary, binary and cast operations are numbered, as well as lengths,
+/// discriminants, fields of SSA locals and reads through shared references to
+/// `Freeze` data, which cannot change while the reference is live.
+///
+/// Comparisons of a value with itself are known to be constant, and so are
+/// `Rvalue`s and operands whose value is a constant. Those are replaced by the
+/// constant, so that `SimplifyBranches` can remove the branches that depend
+/// on them.
+pub struct GlobalValueNumbering;
+
+impl<'tcx> MirPass<'tcx> for GlobalValueNumbering {
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        if tcx.sess.mir_opt_level() < 3 {
+            return;
+        }
+
+        let def_id = body.source.def_id();
+        trace!("Running GlobalValueNumbering on {:?}", def_id);
+
+        if !tcx.consider_optimizing(|| format!("GlobalValueNumbering {:?}", def_id)) {
+            return;
+        }
+
+        let param_env = tcx.param_env_reveal_all_normalized(def_id);
+        let dominators = body.dominators();
+        let ssa = SsaLocals::new(body, &dominators);
+        let blocks: Vec<_> = traversal::reverse_postorder(body).map(|(block, _)| block).collect();
+
+        let mut state = VnState::new(tcx, param_env, body, &ssa, &dominators);
+        let (basic_blocks, local_decls) = body.basic_blocks_and_local_decls_mut();
+        for block in blocks {
+            let data = &mut basic_blocks[block];
+            for (statement_index, statement) in data.statements.iter_mut().enumerate() {
+                let location = Location { block, statement_index };
+                let span = statement.source_info.span;
+                if let StatementKind::Assign(box (place, rvalue)) = &mut statement.kind {
+                    state.visit_assign(local_decls, *place, rvalue, span, location);
+                }
+            }
+
+            let terminator = data.terminator_mut();
+            let span = terminator.source_info.span;
+            match &mut terminator.kind {
+                TerminatorKind::Call { destination: Some((place, _)), .. } => {
+                    state.define_opaque(local_decls, *place);
+                }
+                TerminatorKind::SwitchInt { discr: operand, .. }
+                | TerminatorKind::Assert { cond: operand, .. } => {
+                    state.replace_with_known_constant(local_decls, operand, span);
+                }
+                _ => {}
+            }
+        }
+
+        let reused = state.reused;
+        if !reused.is_empty() {
+            ReuseLocals { tcx, reused }.visit_body(body);
+        }
+    }
+}
+
+/// How a local is defined.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Definition {
+    /// The local isn't defined anywhere.
+    None,
+    /// The local is an argument of the function.
+    Arg,
+    /// The local is defined by a single assignment or call, at this location.
+    At(Location),
+    /// The local is defined several times, or in a way that isn't tracked,
+    /// like through a borrow.
+    Many,
+}
+
+/// The locals that are assigned exactly once, before all of their uses.
+struct SsaLocals {
+    definitions: IndexVec<Local, Definition>,
+}
+
+impl SsaLocals {
+    fn new(body: &Body<'_>, dominators: &Dominators<BasicBlock>) -> SsaLocals {
+        let mut definitions = IndexVec::from_elem(Definition::None, &body.local_decls);
+        for arg in body.args_iter() {
+            definitions[arg] = Definition::Arg;
+        }
+        definitions[RETURN_PLACE] = Definition::Many;
+
+        let mut visitor = SsaVisitor { definitions, uses: Vec::new() };
+        visitor.visit_body(body);
+        let SsaVisitor { mut definitions, uses } = visitor;
+
+        for (local, location) in uses {
+            // Unreachable uses never observe any value.
+            if !dominators.is_reachable(location.block) {
+                continue;
+            }
+            let dominates = match definitions[local] {
+                Definition::Arg | Definition::Many => continue,
+                Definition::None => false,
+                Definition::At(definition) => {
+                    definition != location && definition.dominates(location, dominators)
+                }
+            };
+            if !dominates {
+                definitions[local] = Definition::Many;
+            }
+        }
+
+        SsaLocals { definitions }
+    }
+
+    fn is_ssa(&self, local: Local) -> bool {
+        matches!(self.definitions[local], Definition::Arg | Definition::At(_))
+    }
+
+    /// Returns `true` if the value `local` holds is available at `location`.
+    fn is_available(
+        &self,
+        local: Local,
+        location: Location,
+        dominators: &Dominators<BasicBlock>,
+    ) -> bool {
+        match self.definitions[local] {
+            Definition::Arg => true,
+            Definition::At(definition) => {
+                definition != location && definition.dominates(location, dominators)
+            }
+            Definition::None | Definition::Many => false,
+        }
+    }
+}
+
+struct SsaVisitor {
+    definitions: IndexVec<Local, Definition>,
+    uses: Vec<(Local, Location)>,
+}
+
+impl SsaVisitor {
+    fn define(&mut self, local: Local, location: Location) {
+        self.definitions[local] = match self.definitions[local] {
+            Definition::None => Definition::At(location),
+            _ => Definition::Many,
+        };
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for SsaVisitor {
+    fn visit_statement(&mut self, statement: &Statement<'tcx>, location: Location) {
+        // `SetDiscriminant` is visited as a store to the whole place, but it
+        // only changes a part of it.
+        if let StatementKind::SetDiscriminant { place, .. } = &statement.kind {
+            self.definitions[place.local] = Definition::Many;
+        }
+        self.super_statement(statement, location);
+    }
+
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, location: Location) {
+        match context {
+            PlaceContext::MutatingUse(MutatingUseContext::Store)
+            | PlaceContext::MutatingUse(MutatingUseContext::Call) => self.define(local, location),
+            PlaceContext::NonMutatingUse(NonMutatingUseContext::Copy)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Move)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Inspect)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Projection) => {
+                self.uses.push((local, location))
+            }
+            PlaceContext::NonUse(_) => {}
+            // Borrows, and any other kind of mutation.
+            _ => self.definitions[local] = Definition::Many,
+        }
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if place.projection.first() == Some(&PlaceElem::Deref) {
+            // Going through a pointer only reads the local holding the pointer.
+            let context = PlaceContext::NonMutatingUse(NonMutatingUseContext::Copy);
+            self.visit_local(&place.local, context, location);
+        } else if !place.projection.is_empty()
+            && (context.is_mutating_use() || context.is_borrow() || is_address_of(context))
+        {
+            // Parts of the local are changed, or may be through a pointer.
+            self.definitions[place.local] = Definition::Many;
+        } else {
+            self.visit_local(&place.local, context, location);
+        }
+        self.visit_projection(place.as_ref(), context, location);
+    }
+}
+
+fn is_address_of(context: PlaceContext) -> bool {
+    matches!(
+        context,
+        PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf)
+            | PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+    )
+}
+
+rustc_index::newtype_index! {
+    struct VnIndex {
+        DEBUG_FORMAT = "v{}"
+    }
+}
+
+/// A value computed by an `Rvalue`, in terms of the values it depends on.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Value<'tcx> {
+    /// A value that is only known to be equal to itself, like an argument or
+    /// the result of a call. The `usize` makes each opaque value unique.
+    Opaque(usize),
+    Constant(&'tcx ty::Const<'tcx>),
+    /// The field of an aggregate value.
+    Field(VnIndex, Field),
+    /// The value behind a shared reference to `Freeze` data.
+    ///
+    /// Two reads through the same reference value give the same value number,
+    /// even if other code runs between them. This is sound because the data
+    /// behind a `&T` with `T: Freeze` contains no `UnsafeCell`, so it can
+    /// neither be mutated nor deallocated as long as the reference is live,
+    /// and the reference is live at least until the later of the two reads,
+    /// which uses it. A write or deallocation in between, through any other
+    /// pointer, would already be undefined behavior.
+    Deref(VnIndex),
+    /// The length of an array value.
+    Len(VnIndex),
+    /// The length of the array or slice a pointer value points to, which only
+    /// depends on the pointer itself.
+    LenOfPointee(VnIndex),
+    Discriminant(VnIndex),
+    UnaryOp(UnOp, VnIndex),
+    BinaryOp(BinOp, VnIndex, VnIndex),
+    CheckedBinaryOp(BinOp, VnIndex, VnIndex),
+    Cast(CastKind, VnIndex, Ty<'tcx>),
+    NullaryOp(NullOp, Ty<'tcx>),
+}
+
+struct VnState<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    ssa: &'a SsaLocals,
+    dominators: &'a Dominators<BasicBlock>,
+    values: IndexVec<VnIndex, Value<'tcx>>,
+    value_indices: FxHashMap<Value<'tcx>, VnIndex>,
+    /// The value of each SSA local whose definition has been visited.
+    local_values: IndexVec<Local, Option<VnIndex>>,
+    /// The SSA locals of `Copy` type holding each value.
+    locals_with_value: FxHashMap<VnIndex, Vec<Local>>,
+    next_opaque: usize,
+    /// The locals that replace a computation somewhere.
+    reused: BitSet<Local>,
+}
+
+impl<'a, 'tcx> VnState<'a, 'tcx> {
+    fn new(
+        tcx: TyCtxt<'tcx>,
+        param_env: ParamEnv<'tcx>,
+        body: &Body<'tcx>,
+        ssa: &'a SsaLocals,
+        dominators: &'a Dominators<BasicBlock>,
+    ) -> Self {
+        let mut state = VnState {
+            tcx,
+            param_env,
+            ssa,
+            dominators,
+            values: IndexVec::new(),
+            value_indices: FxHashMap::default(),
+            local_values: IndexVec::from_elem(None, &body.local_decls),
+            locals_with_value: FxHashMap::default(),
+            next_opaque: 0,
+            reused: BitSet::new_empty(body.local_decls.len()),
+        };
+        for arg in body.args_iter() {
+            if ssa.is_ssa(arg) {
+                let value = state.new_opaque();
+                state.define(&body.local_decls, arg, value);
+            }
+        }
+        state
+    }
+
+    fn insert(&mut self, value: Value<'tcx>) -> VnIndex {
+        let values = &mut self.values;
+        *self.value_indices.entry(value).or_insert_with(|| values.push(value))
+    }
+
+    fn new_opaque(&mut self) -> VnIndex {
+        let value = Value::Opaque(self.next_opaque);
+        self.next_opaque += 1;
+        self.insert(value)
+    }
+
+    fn define(&mut self, local_decls: &LocalDecls<'tcx>, local: Local, value: VnIndex) {
+        self.local_values[local] = Some(value);
+        // Only locals of `Copy` type can be read again instead of recomputing
+        // their value.
+        let ty = local_decls[local].ty;
+        if ty.is_copy_modulo_regions(self.tcx.at(DUMMY_SP), self.param_env) {
+            self.locals_with_value.entry(value).or_default().push(local);
+        }
+    }
+
+    /// Gives the SSA local defined by a call a fresh value.
+    fn define_opaque(&mut self, local_decls: &LocalDecls<'tcx>, place: Place<'tcx>) {
+        if let Some(local) = place.as_local() {
+            if self.ssa.is_ssa(local) {
+                let value = self.new_opaque();
+                self.define(local_decls, local, value);
+            }
+        }
+    }
+
+    fn visit_assign(
+        &mut self,
+        local_decls: &LocalDecls<'tcx>,
+        place: Place<'tcx>,
+        rvalue: &mut Rvalue<'tcx>,
+        span: Span,
+        location: Location,
+    ) {
+        let value = self.rvalue_value(local_decls, rvalue);
+
+        if let Some(value) = value {
+            if !matches!(rvalue, Rvalue::Use(_)) {
+                if let Some(replacement) = self.replacement(value, span, location) {
+                    debug!("replacing {:?} at {:?} by {:?}", rvalue, location, replacement);
+                    *rvalue = Rvalue::Use(replacement);
+                }
+            }
+        }
+
+        if let Some(local) = place.as_local() {
+            if self.ssa.is_ssa(local) {
+                let value = match value {
+                    Some(value) => value,
+                    None => self.new_opaque(),
+                };
+                self.define(local_decls, local, value);
+            }
+        }
+    }
+
+    /// Replaces `operand` by a constant if its value is known to be one.
+    fn replace_with_known_constant(
+        &mut self,
+        local_decls: &LocalDecls<'tcx>,
+        operand: &mut Operand<'tcx>,
+        span: Span,
+    ) {
+        if let Operand::Constant(_) = operand {
+            return;
+        }
+        if let Some(value) = self.operand_value(local_decls, operand) {
+            if let Value::Constant(literal) = self.values[value] {
+                *operand = constant_operand(literal, span);
+            }
+        }
+    }
+
+    /// Returns an operand that computes `value` without recomputing it, if
+    /// there is one at `location`.
+    fn replacement(
+        &mut self,
+        value: VnIndex,
+        span: Span,
+        location: Location,
+    ) -> Option<Operand<'tcx>> {
+        if let Value::Constant(literal) = self.values[value] {
+            return Some(constant_operand(literal, span));
+        }
+
+        let ssa = self.ssa;
+        let dominators = self.dominators;
+        let local = *self
+            .locals_with_value
+            .get(&value)?
+            .iter()
+            .find(|&&local| ssa.is_available(local, location, dominators))?;
+        self.reused.insert(local);
+        Some(Operand::Copy(local.into()))
+    }
+
+    fn operand_value(
+        &mut self,
+        local_decls: &LocalDecls<'tcx>,
+        operand: &Operand<'tcx>,
+    ) -> Option<VnIndex> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.place_value(local_decls, *place),
+            Operand::Constant(constant) => Some(self.insert(Value::Constant(constant.literal))),
+        }
+    }
+
+    /// Returns the value of `place`, if it only depends on SSA locals.
+    fn place_value(
+        &mut self,
+        local_decls: &LocalDecls<'tcx>,
+        place: Place<'tcx>,
+    ) -> Option<VnIndex> {
+        let mut value = self.local_values[place.local]?;
+        for (i, elem) in place.projection.iter().enumerate() {
+            value = match elem {
+                // Only the first projection can be a deref of an SSA local, see
+                // `Value::Deref` for why loads through it can be merged.
+                ProjectionElem::Deref
+                    if i == 0 && self.is_frozen_reference(local_decls[place.local].ty) =>
+                {
+                    self.insert(Value::Deref(value))
+                }
+                ProjectionElem::Field(field, _) => self.insert(Value::Field(value, field)),
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// Returns `true` if `ty` is a shared reference to data that cannot change
+    /// while the reference is live.
+    fn is_frozen_reference(&self, ty: Ty<'tcx>) -> bool {
+        match ty.kind() {
+            ty::Ref(_, pointee, Mutability::Not) => {
+                pointee.is_freeze(self.tcx.at(DUMMY_SP), self.param_env)
+            }
+            _ => false,
+        }
+    }
+
+    fn rvalue_value(
+        &mut self,
+        local_decls: &LocalDecls<'tcx>,
+        rvalue: &Rvalue<'tcx>,
+    ) -> Option<VnIndex> {
+        let value = match *rvalue {
+            Rvalue::Use(ref operand) => return self.operand_value(local_decls, operand),
+            Rvalue::Len(place) => match place.as_ref() {
+                PlaceRef { local, projection: [ProjectionElem::Deref] } => {
+                    Value::LenOfPointee(self.local_values[local]?)
+                }
+                _ => Value::Len(self.place_value(local_decls, place)?),
+            },
+            Rvalue::Discriminant(place) => {
+                Value::Discriminant(self.place_value(local_decls, place)?)
+            }
+            Rvalue::UnaryOp(op, ref operand) => {
+                Value::UnaryOp(op, self.operand_value(local_decls, operand)?)
+            }
+            Rvalue::BinaryOp(op, box (ref lhs, ref rhs)) => {
+                let lhs_value = self.operand_value(local_decls, lhs)?;
+                let rhs_value = self.operand_value(local_decls, rhs)?;
+                if lhs_value == rhs_value {
+                    if let Some(result) =
+                        self.compare_with_itself(op, lhs.ty(local_decls, self.tcx))
+                    {
+                        let literal = ty::Const::from_bool(self.tcx, result);
+                        return Some(self.insert(Value::Constant(literal)));
+                    }
+                }
+                Value::BinaryOp(op, lhs_value, rhs_value)
+            }
+            Rvalue::CheckedBinaryOp(op, box (ref lhs, ref rhs)) => Value::CheckedBinaryOp(
+                op,
+                self.operand_value(local_decls, lhs)?,
+                self.operand_value(local_decls, rhs)?,
+            ),
+            Rvalue::Cast(kind, ref operand, ty) => {
+                Value::Cast(kind, self.operand_value(local_decls, operand)?, ty)
+            }
+            Rvalue::NullaryOp(op @ NullOp::SizeOf, ty) => Value::NullaryOp(op, ty),
+            // These create new values or allocations, or read from places
+            // that may change.
+            Rvalue::NullaryOp(NullOp::Box, _)
+            | Rvalue::Repeat(..)
+            | Rvalue::Ref(..)
+            | Rvalue::ThreadLocalRef(..)
+            | Rvalue::AddressOf(..)
+            | Rvalue::Aggregate(..) => return None,
+        };
+        Some(self.insert(value))
+    }
+
+    /// Returns the result of comparing a value of type `ty` with itself, if
+    /// `op` is a comparison whose result is known for that type.
+    fn compare_with_itself(&self, op: BinOp, ty: Ty<'tcx>) -> Option<bool> {
+        // Floats aren't equal to themselves if they are NaN.
+        if !(ty.is_integral() || ty.is_bool() || ty.is_char()) {
+            return None;
+        }
+        match op {
+            BinOp::Eq | BinOp::Le | BinOp::Ge => Some(true),
+            BinOp::Ne | BinOp::Lt | BinOp::Gt => Some(false),
+            _ => None,
+        }
+    }
+}
+
+fn constant_operand<'tcx>(literal: &'tcx ty::Const<'tcx>, span: Span) -> Operand<'tcx> {
+    Operand::Constant(box Constant { span, user_ty: None, literal })
+}
+
+/// Makes the locals that replace computations available wherever they are
+/// read: their storage markers are removed, since their storage may have been
+/// dead at the new reads, and moves out of them become copies, since the value
+/// must stay intact for the new reads.
+struct ReuseLocals<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    reused: BitSet<Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ReuseLocals<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, location: Location) {
+        if let Operand::Move(place) = *operand {
+            if place.as_local().map_or(false, |local| self.reused.contains(local)) {
+                *operand = Operand::Copy(place);
+            }
+        }
+        self.super_operand(operand, location);
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement<'tcx>, location: Location) {
+        match statement.kind {
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local)
+                if self.reused.contains(local) =>
+            {
+                statement.make_nop()
+            }
+            _ => self.super_statement(statement, location),
+        }
+    }
+}
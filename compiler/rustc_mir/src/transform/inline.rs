@@ -2,6 +2,8 @@
 
 use rustc_attr::InlineAttr;
 use rustc_hir as hir;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_index::bit_set::BitSet;
 use rustc_index::vec::Idx;
 use rustc_middle::middle::codegen_fn_attrs::{CodegenFnAttrFlags, CodegenFnAttrs};
@@ -9,6 +11,9 @@
 use rustc_middle::mir::*;
 use rustc_middle::ty::subst::Subst;
 use rustc_middle::ty::{self, ConstKind, Instance, InstanceDef, ParamEnv, Ty, TyCtxt};
+use rustc_session::config::CrateType;
+use rustc_session::lint::builtin::MISSING_INLINE_HINT;
+use rustc_session::lint::Level;
 use rustc_span::{hygiene::ExpnKind, ExpnData, Span};
 use rustc_target::spec::abi::Abi;
 
@@ -37,6 +42,13 @@ struct CallSite<'tcx> {
     source_info: SourceInfo,
 }
 
+/// The cost of inlining a callee, and the threshold it is compared against.
+#[derive(Copy, Clone, Debug)]
+struct InlineCost {
+    cost: usize,
+    threshold: usize,
+}
+
 /// Returns true if MIR inlining is enabled in the current compilation session.
 crate fn is_enabled(tcx: TyCtxt<'_>) -> bool {
     if tcx.sess.opts.debugging_opts.instrument_coverage {
@@ -57,20 +69,128 @@ struct CallSite<'tcx> {
 
 impl<'tcx> MirPass<'tcx> for Inline {
     fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
-        if !is_enabled(tcx) {
-            return;
+        if is_enabled(tcx) {
+            let span = trace_span!("inline", body = %tcx.def_path_str(body.source.def_id()));
+            let _guard = span.enter();
+            if inline(tcx, body) {
+                debug!("running simplify cfg on {:?}", body.source);
+                CfgSimplifier::new(body).simplify();
+                remove_dead_blocks(body);
+            }
         }
 
-        let span = trace_span!("inline", body = %tcx.def_path_str(body.source.def_id()));
-        let _guard = span.enter();
-        if inline(tcx, body) {
-            debug!("running simplify cfg on {:?}", body.source);
-            CfgSimplifier::new(body).simplify();
-            remove_dead_blocks(body);
-        }
+        // This is checked after inlining into `body`, as that is what other crates would inline.
+        lint_missing_inline_hint(tcx, body);
+    }
+}
+
+/// Returns the threshold the cost of inlining a callee is compared against, before the bonus
+/// given to callees with few blocks.
+fn base_threshold(tcx: TyCtxt<'_>, requests_inline: bool) -> usize {
+    if requests_inline {
+        tcx.sess.opts.debugging_opts.inline_mir_hint_threshold.unwrap_or(100)
+    } else {
+        tcx.sess.opts.debugging_opts.inline_mir_threshold.unwrap_or(50)
     }
 }
 
+/// Lints `body` if it is a small exported function that other crates cannot inline, because it
+/// is not generic and has no `#[inline]` attribute. "Small" means that the MIR inliner would
+/// inline it if it had the attribute.
+///
+/// As this runs as part of building the optimized MIR, the lint is not emitted when there is no
+/// codegen, nor for functions whose optimized MIR is reused by an incremental build.
+fn lint_missing_inline_hint(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) {
+    let def_id = match body.source.def_id().as_local() {
+        Some(def_id) => def_id,
+        None => return,
+    };
+    if body.source.promoted.is_some()
+        || !matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+    {
+        return;
+    }
+
+    let hir_id = tcx.hir().local_def_id_to_hir_id(def_id);
+    if tcx.lint_level_at_node(MISSING_INLINE_HINT, hir_id).0 == Level::Allow {
+        return;
+    }
+
+    // Only libraries have functions that other crates call.
+    let is_library =
+        tcx.sess.crate_types().iter().any(|ty| matches!(ty, CrateType::Rlib | CrateType::Dylib));
+    if !is_library || !tcx.reachable_set(LOCAL_CRATE).contains(&def_id) {
+        return;
+    }
+
+    // Generic functions are already available to other crates. Functions exported under a
+    // symbol name are meant to be called, and naked and cold ones are never inlined.
+    let codegen_fn_attrs = tcx.codegen_fn_attrs(def_id);
+    if codegen_fn_attrs.inline != InlineAttr::None
+        || codegen_fn_attrs.contains_extern_indicator()
+        || codegen_fn_attrs.flags.intersects(CodegenFnAttrFlags::NAKED | CodegenFnAttrFlags::COLD)
+        || tcx.generics_of(def_id).requires_monomorphization(tcx)
+    {
+        return;
+    }
+
+    let inliner = Inliner {
+        tcx,
+        param_env: tcx.param_env_reveal_all_normalized(def_id),
+        codegen_fn_attrs,
+        hir_id,
+        history: Vec::new(),
+        changed: false,
+    };
+    let callsite = CallSite {
+        callee: Instance::mono(tcx, def_id.to_def_id()),
+        fn_sig: tcx.fn_sig(def_id),
+        block: START_BLOCK,
+        target: None,
+        source_info: SourceInfo::outermost(body.span),
+    };
+    let cost = match inliner.check_mir_body(&callsite, body, base_threshold(tcx, true)) {
+        Ok(cost) if cost.cost <= cost.threshold => cost,
+        _ => return,
+    };
+
+    // Inlining a body that calls a function other crates cannot inline either only moves the
+    // call, so `#[inline]` would have to be added to the callee first.
+    if calls_non_inline_local_fn(tcx, body) {
+        return;
+    }
+
+    tcx.struct_span_lint_hir(MISSING_INLINE_HINT, hir_id, tcx.def_span(def_id), |lint| {
+        lint.build(&format!(
+            "`{}` is small enough to be inlined into other crates",
+            tcx.def_path_str(def_id.to_def_id())
+        ))
+        .note(&format!(
+            "its inlining cost is {}, the threshold for `#[inline]` functions is {}",
+            cost.cost, cost.threshold
+        ))
+        .help("add `#[inline]` to make its body available to other crates")
+        .emit()
+    });
+}
+
+/// Returns true if `body` calls a function of the local crate that is neither generic nor
+/// `#[inline]`, so that its body isn't available to other crates.
+fn calls_non_inline_local_fn(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> bool {
+    body.basic_blocks().iter().any(|block| match &block.terminator().kind {
+        TerminatorKind::Call { func, .. } => match *func.ty(body, tcx).kind() {
+            ty::FnDef(callee, _) => {
+                callee.is_local()
+                    && matches!(tcx.def_kind(callee), DefKind::Fn | DefKind::AssocFn)
+                    && tcx.codegen_fn_attrs(callee).inline == InlineAttr::None
+                    && !tcx.generics_of(callee).requires_monomorphization(tcx)
+            }
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
 fn inline(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) -> bool {
     let def_id = body.source.def_id();
     let hir_id = tcx.hir().local_def_id_to_hir_id(def_id.expect_local());
@@ -125,7 +245,13 @@ fn process_blocks(&mut self, caller_body: &mut Body<'tcx>, blocks: Range<BasicBl
             let span = trace_span!("process_blocks", %callsite.callee, ?bb);
             let _guard = span.enter();
 
-            match self.try_inlining(caller_body, &callsite) {
+            let mut cost = None;
+            let result = self.try_inlining(caller_body, &callsite, &mut cost);
+            if self.tcx.sess.opts.debugging_opts.inline_mir_report {
+                self.report(caller_body.source.def_id(), &callsite, result.clone().map(drop), cost);
+            }
+
+            match result {
                 Err(reason) => {
                     debug!("not-inlined {} [{}]", callsite.callee, reason);
                     continue;
@@ -141,19 +267,47 @@ fn process_blocks(&mut self, caller_body: &mut Body<'tcx>, blocks: Range<BasicBl
         }
     }
 
+    /// Prints a note with the inlining decision taken for `callsite`, for `-Z inline-mir-report`.
+    fn report(
+        &self,
+        caller: DefId,
+        callsite: &CallSite<'tcx>,
+        result: Result<(), &str>,
+        cost: Option<InlineCost>,
+    ) {
+        let caller = self.tcx.def_path_str(caller);
+        let cost = match cost {
+            Some(InlineCost { cost, threshold }) => {
+                format!(" (cost {}, threshold {})", cost, threshold)
+            }
+            None => String::new(),
+        };
+        let msg = match result {
+            Ok(()) => format!("inlined `{}` into `{}`{}", callsite.callee, caller, cost),
+            Err(reason) => {
+                format!("not inlining `{}` into `{}`: {}{}", callsite.callee, caller, reason, cost)
+            }
+        };
+        self.tcx.sess.span_note_without_error(callsite.source_info.span, &msg);
+    }
+
     /// Attempts to inline a callsite into the caller body. When successful returns basic blocks
     /// containing the inlined body. Otherwise returns an error describing why inlining didn't take
-    /// place.
+    /// place. The cost of inlining is stored in `cost` if it was computed.
     fn try_inlining(
         &self,
         caller_body: &mut Body<'tcx>,
         callsite: &CallSite<'tcx>,
+        cost: &mut Option<InlineCost>,
     ) -> Result<std::ops::Range<BasicBlock>, &'static str> {
         let callee_attrs = self.tcx.codegen_fn_attrs(callsite.callee.def_id());
         self.check_codegen_attributes(callsite, callee_attrs)?;
         self.check_mir_is_available(caller_body, &callsite.callee)?;
         let callee_body = self.tcx.instance_mir(callsite.callee.def);
-        self.check_mir_body(callsite, callee_body, callee_attrs)?;
+        let threshold = base_threshold(self.tcx, callee_attrs.requests_inline());
+        let inline_cost = self.check_mir_body(callsite, callee_body, threshold)?;
+        *cost = Some(inline_cost);
+        self.check_cost(callsite, callee_attrs, inline_cost)?;
 
         if !self.tcx.consider_optimizing(|| {
             format!("Inline {:?} into {}", callee_body.span, callsite.callee)
@@ -337,23 +491,19 @@ fn check_codegen_attributes(
         Ok(())
     }
 
-    /// Returns inlining decision that is based on the examination of callee MIR body.
+    /// Returns the cost of inlining the callee, based on the examination of callee MIR body, and
+    /// the threshold it should be compared against, starting from `threshold`. Returns an error if
+    /// the callee body makes inlining impossible.
     /// Assumes that codegen attributes have been checked for compatibility already.
     #[instrument(level = "debug", skip(self, callee_body))]
     fn check_mir_body(
         &self,
         callsite: &CallSite<'tcx>,
         callee_body: &Body<'tcx>,
-        callee_attrs: &CodegenFnAttrs,
-    ) -> Result<(), &'static str> {
+        mut threshold: usize,
+    ) -> Result<InlineCost, &'static str> {
         let tcx = self.tcx;
 
-        let mut threshold = if callee_attrs.requests_inline() {
-            self.tcx.sess.opts.debugging_opts.inline_mir_hint_threshold.unwrap_or(100)
-        } else {
-            self.tcx.sess.opts.debugging_opts.inline_mir_threshold.unwrap_or(50)
-        };
-
         // Give a bonus functions with a small number of blocks,
         // We normally have two or three blocks for even
         // very small functions.
@@ -478,6 +628,16 @@ fn check_mir_body(
             }
         }
 
+        Ok(InlineCost { cost, threshold })
+    }
+
+    /// Returns inlining decision that is based on the cost of inlining the callee.
+    fn check_cost(
+        &self,
+        callsite: &CallSite<'tcx>,
+        callee_attrs: &CodegenFnAttrs,
+        InlineCost { cost, threshold }: InlineCost,
+    ) -> Result<(), &'static str> {
         if let InlineAttr::Always = callee_attrs.inline {
             debug!("INLINING {:?} because inline(always) [cost={}]", callsite, cost);
             Ok(())
@@ -0,0 +1,275 @@
+//! See the docs for [`JumpThreading`].
+
+use crate::transform::MirPass;
+use rustc_index::bit_set::BitSet;
+use rustc_middle::mir::*;
+use rustc_middle::ty::{ParamEnv, Ty, TyCtxt};
+use rustc_target::abi::VariantIdx;
+
+use super::dest_prop::ever_borrowed_locals;
+use super::simplify::simplify_cfg;
+
+/// Redirects jumps to a `SwitchInt` whose outcome is already known in the
+/// block jumping to it.
+///
+/// When a block ends in a `goto` to a block that switches on a value that the
+/// first block has just set, the first block can jump straight to the target
+/// the switch would pick:
+///
+/// ```text
+/// bb1: {                                      bb1: {
+///     ((_3 as Ok).0: u32) = move _4;              ((_3 as Ok).0: u32) = move _4;
+///     discriminant(_3) = 0;                       discriminant(_3) = 0;
+///     goto -> bb3;                                _5 = discriminant(_3);
+/// }                                               goto -> bb4;
+///                                      =>     }
+/// bb3: {
+///     _5 = discriminant(_3);
+///     switchInt(move _5) -> [0: bb4, 1: bb5, otherwise: bb6];
+/// }
+/// ```
+///
+/// This is the shape left behind by constructing an `Option` or `Result` and
+/// matching on it right away, which is what chains of `?` do once the
+/// `Try` impls are inlined. The statements of the switching block are copied
+/// to the end of the redirected block, so the switching block is left intact
+/// for its other predecessors.
+///
+/// The switched on value is known if it is a local assigned a constant, or the
+/// discriminant of a place that is set by `SetDiscriminant` or by an enum
+/// aggregate, and if nothing in between may change it.
+pub struct JumpThreading;
+
+/// Threading a jump can expose another one, as in cascades of `?`, so the pass
+/// is repeated. Jumps around a loop can be threaded forever, so the number of
+/// rounds is bounded.
+const MAX_ROUNDS: usize = 8;
+
+/// Switching blocks with more statements than this are not duplicated.
+const MAX_DUPLICATED_STATEMENTS: usize = 8;
+
+impl<'tcx> MirPass<'tcx> for JumpThreading {
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        if tcx.sess.mir_opt_level() < 3 {
+            return;
+        }
+
+        let def_id = body.source.def_id();
+        trace!("Running JumpThreading on {:?}", def_id);
+
+        let finder = ThreadFinder {
+            tcx,
+            param_env: tcx.param_env_reveal_all_normalized(def_id),
+            borrowed: ever_borrowed_locals(body),
+        };
+
+        let mut changed = false;
+        'rounds: for _ in 0..MAX_ROUNDS {
+            let threads = finder.find_threads(body);
+            if threads.is_empty() {
+                break;
+            }
+
+            for thread in threads {
+                if !tcx.consider_optimizing(|| format!("JumpThreading {:?}", thread)) {
+                    break 'rounds;
+                }
+                debug!("threading {:?}", thread);
+
+                let statements = body.basic_blocks()[thread.switch_block].statements.clone();
+                let predecessor = &mut body.basic_blocks_mut()[thread.predecessor];
+                predecessor.statements.extend(statements);
+                predecessor.terminator_mut().kind = TerminatorKind::Goto { target: thread.target };
+                changed = true;
+            }
+        }
+
+        // The switching blocks may have become unreachable.
+        if changed {
+            simplify_cfg(body);
+        }
+    }
+}
+
+/// A `goto` from `predecessor` to `switch_block` that can go to `target`
+/// directly.
+#[derive(Debug)]
+struct Thread {
+    predecessor: BasicBlock,
+    switch_block: BasicBlock,
+    target: BasicBlock,
+}
+
+/// The value a `SwitchInt` depends on, as known at the start of its block.
+#[derive(Copy, Clone, Debug)]
+enum Condition<'tcx> {
+    /// The value of a local.
+    Value(Local),
+    /// The discriminant of a place.
+    Discriminant(Place<'tcx>),
+}
+
+impl Condition<'_> {
+    fn local(self) -> Local {
+        match self {
+            Condition::Value(local) => local,
+            Condition::Discriminant(place) => place.local,
+        }
+    }
+}
+
+struct ThreadFinder<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    /// The locals that may be changed through a pointer.
+    borrowed: BitSet<Local>,
+}
+
+impl<'tcx> ThreadFinder<'tcx> {
+    fn find_threads(&self, body: &Body<'tcx>) -> Vec<Thread> {
+        let mut threads = Vec::new();
+        for (switch_block, data) in body.basic_blocks().iter_enumerated() {
+            let (discr, switch_ty, targets) = match &data.terminator().kind {
+                TerminatorKind::SwitchInt { discr, switch_ty, targets } => {
+                    (discr, switch_ty, targets)
+                }
+                _ => continue,
+            };
+            if data.statements.len() > MAX_DUPLICATED_STATEMENTS {
+                continue;
+            }
+            let condition = match self.switch_condition(data, discr) {
+                Some(condition) => condition,
+                None => continue,
+            };
+
+            for &predecessor in &body.predecessors()[switch_block] {
+                let predecessor_data = &body.basic_blocks()[predecessor];
+                if !matches!(predecessor_data.terminator().kind, TerminatorKind::Goto { .. }) {
+                    continue;
+                }
+                let value = match self.known_value(body, predecessor_data, condition, *switch_ty) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let target = targets
+                    .iter()
+                    .find(|&(target_value, _)| target_value == value)
+                    .map_or_else(|| targets.otherwise(), |(_, target)| target);
+                threads.push(Thread { predecessor, switch_block, target });
+            }
+        }
+        threads
+    }
+
+    /// Returns what the `SwitchInt` on `discr` ending `data` depends on, if
+    /// the statements of `data` can be duplicated into its predecessors.
+    fn switch_condition(
+        &self,
+        data: &BasicBlockData<'tcx>,
+        discr: &Operand<'tcx>,
+    ) -> Option<Condition<'tcx>> {
+        let discr_local = discr.place()?.as_local()?;
+        let mut condition = Condition::Value(discr_local);
+        for statement in data.statements.iter().rev() {
+            match (condition, &statement.kind) {
+                (
+                    Condition::Value(local),
+                    StatementKind::Assign(box (place, Rvalue::Discriminant(discr_place))),
+                ) if place.as_local() == Some(local)
+                    && discr_place.local != local
+                    && !discr_place.is_indirect()
+                    && !has_index(discr_place) =>
+                {
+                    condition = Condition::Discriminant(*discr_place);
+                }
+                _ if self.may_change(statement, condition.local()) => return None,
+                _ => {}
+            }
+        }
+        Some(condition)
+    }
+
+    /// Returns the value of `condition` at the end of `data`, if it is known.
+    fn known_value(
+        &self,
+        body: &Body<'tcx>,
+        data: &BasicBlockData<'tcx>,
+        condition: Condition<'tcx>,
+        switch_ty: Ty<'tcx>,
+    ) -> Option<u128> {
+        for statement in data.statements.iter().rev() {
+            match (condition, &statement.kind) {
+                (
+                    Condition::Value(local),
+                    StatementKind::Assign(box (place, Rvalue::Use(Operand::Constant(constant)))),
+                ) if place.as_local() == Some(local) => {
+                    return constant.literal.try_eval_bits(self.tcx, self.param_env, switch_ty);
+                }
+                (
+                    Condition::Discriminant(discr_place),
+                    StatementKind::SetDiscriminant { place, variant_index },
+                ) if **place == discr_place => {
+                    return self.discriminant_value(body, discr_place, *variant_index);
+                }
+                (
+                    Condition::Discriminant(discr_place),
+                    StatementKind::Assign(box (
+                        place,
+                        Rvalue::Aggregate(box AggregateKind::Adt(_, variant_index, ..), _),
+                    )),
+                ) if *place == discr_place => {
+                    return self.discriminant_value(body, discr_place, *variant_index);
+                }
+                _ if self.may_change(statement, condition.local()) => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn discriminant_value(
+        &self,
+        body: &Body<'tcx>,
+        place: Place<'tcx>,
+        variant_index: VariantIdx,
+    ) -> Option<u128> {
+        // Generators are left alone, as their discriminants depend on their
+        // layout, which is computed from their optimized MIR.
+        let adt_def = place.ty(body, self.tcx).ty.ty_adt_def()?;
+        if !adt_def.is_enum() {
+            return None;
+        }
+        Some(adt_def.discriminant_for_variant(self.tcx, variant_index).val)
+    }
+
+    /// Returns `true` if `statement` may change the value of `local`.
+    ///
+    /// For a `Condition::Discriminant`, only writes to the base local of the
+    /// place are checked, which is why `switch_condition` rejects places that
+    /// are indexed by another local.
+    fn may_change(&self, statement: &Statement<'tcx>, local: Local) -> bool {
+        let writes = |place: &Place<'tcx>| {
+            place.local == local || (place.is_indirect() && self.borrowed.contains(local))
+        };
+        match &statement.kind {
+            StatementKind::Assign(box (place, _)) => writes(place),
+            StatementKind::SetDiscriminant { place, .. } => writes(place),
+            StatementKind::StorageLive(storage_local)
+            | StatementKind::StorageDead(storage_local) => *storage_local == local,
+            StatementKind::FakeRead(..)
+            | StatementKind::AscribeUserType(..)
+            | StatementKind::Coverage(..)
+            | StatementKind::Nop => false,
+            StatementKind::Retag(..)
+            | StatementKind::LlvmInlineAsm(..)
+            | StatementKind::CopyNonOverlapping(..) => true,
+        }
+    }
+}
+
+/// Returns `true` if `place` is indexed by a local, in which case the element
+/// it refers to can change without the place itself being written to.
+fn has_index(place: &Place<'_>) -> bool {
+    place.projection.iter().any(|elem| matches!(elem, ProjectionElem::Index(_)))
+}
@@ -33,8 +33,10 @@
 pub mod elaborate_drops;
 pub mod function_item_references;
 pub mod generator;
+pub mod gvn;
 pub mod inline;
 pub mod instcombine;
+pub mod jump_threading;
 pub mod lower_intrinsics;
 pub mod match_branches;
 pub mod multiple_return_terminators;
@@ -50,6 +52,7 @@
 pub mod simplify_branches;
 pub mod simplify_comparison_integral;
 pub mod simplify_try;
+pub mod sroa;
 pub mod uninhabited_enum_branching;
 pub mod unreachable_prop;
 pub mod validate;
@@ -500,8 +503,11 @@ fn run_optimization_passes<'tcx>(tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
         // inst combine is after MatchBranchSimplification to clean up Ne(_1, false)
         &multiple_return_terminators::MultipleReturnTerminators,
         &instcombine::InstCombine,
+        &sroa::ScalarReplacementOfAggregates,
         &const_prop::ConstProp,
+        &gvn::GlobalValueNumbering,
         &simplify_branches::SimplifyBranches::new("after-const-prop"),
+        &jump_threading::JumpThreading,
         &early_otherwise_branch::EarlyOtherwiseBranch,
         &simplify_comparison_integral::SimplifyComparisonIntegral,
         &simplify_try::SimplifyArmIdentity,
@@ -0,0 +1,281 @@
+//! See the docs for [`ScalarReplacementOfAggregates`].
+
+use crate::dataflow::impls::MaybeBorrowedLocals;
+use crate::dataflow::Analysis;
+use crate::transform::MirPass;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+use rustc_middle::mir::visit::{MutVisitor, NonUseContext, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_session::config::DebugInfo;
+use std::mem;
+
+/// Splits locals of tuple and struct type into one local per field.
+///
+/// A local is split if its address is never taken, as determined by the
+/// `MaybeBorrowedLocals` analysis, and if it is only ever used through a field
+/// projection. Every `_1.0` is then replaced by a fresh local `_2`, every
+/// `_1.1` by `_3`, and so on, and the storage markers of `_1` are replaced by
+/// storage markers for each of the new locals. Fields that are written but
+/// never read, e.g. the remains of a tuple that was only constructed to be
+/// taken apart again, leave dead assignments behind that `SimplifyLocals`
+/// removes later on.
+///
+/// ```rust
+/// fn foo(a: u32, b: u32) -> u32 {
+///     let t = (a, b);
+///     t.0
+/// }
+/// ```
+///
+/// Since fields can be aggregates themselves, the pass is repeated until no
+/// more locals can be split.
+///
+/// Debuginfo can only describe a user variable that lives in a single local,
+/// so locals that are referred to by debuginfo as a whole are only split when
+/// no debuginfo is emitted, in which case their debuginfo is dropped.
+///
+/// This relies on the `Deaggregator` having run before: an aggregate rvalue
+/// like `_1 = (move _2, move _3)` assigns `_1` as a whole, which would keep
+/// `_1` from being split, while its deaggregated form `(_1.0: u32) = move _2;
+/// (_1.1: u32) = move _3` only uses field projections.
+pub struct ScalarReplacementOfAggregates;
+
+impl<'tcx> MirPass<'tcx> for ScalarReplacementOfAggregates {
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
+        if tcx.sess.mir_opt_level() < 3 {
+            return;
+        }
+
+        trace!("Running ScalarReplacementOfAggregates on {:?}", body.source);
+
+        loop {
+            let split_fields = compute_split_fields(tcx, body);
+            if split_fields.iter().all(|fields| fields.is_empty()) {
+                break;
+            }
+
+            let def_id = body.source.def_id();
+            if !tcx.consider_optimizing(|| format!("ScalarReplacementOfAggregates {:?}", def_id)) {
+                break;
+            }
+
+            let replacements = allocate_replacements(body, split_fields);
+            replace_split_locals(tcx, body, &replacements);
+        }
+    }
+}
+
+/// For each local to split, the local replacing each of its used fields.
+type Replacements = IndexVec<Local, Option<FxIndexMap<Field, Local>>>;
+
+/// Returns `true` if locals of type `ty` can be split into one local per field.
+fn is_splittable(ty: Ty<'_>) -> bool {
+    match ty.kind() {
+        ty::Tuple(..) => true,
+        // The layout of SIMD and packed structs matters beyond the values of
+        // their fields, so they are left alone.
+        ty::Adt(adt_def, _) => {
+            adt_def.is_struct() && !adt_def.repr.simd() && !adt_def.repr.packed()
+        }
+        _ => false,
+    }
+}
+
+/// Returns the locals whose address is taken anywhere in `body`.
+fn borrowed_locals<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> BitSet<Local> {
+    let mut cursor = MaybeBorrowedLocals::all_borrows()
+        .into_engine(tcx, body)
+        .iterate_to_fixpoint()
+        .into_results_cursor(body);
+
+    // A local is borrowed right after the statement borrowing it, so it is
+    // enough to look at the state after each statement and terminator.
+    let mut borrowed = BitSet::new_empty(body.local_decls.len());
+    for (block, data) in body.basic_blocks().iter_enumerated() {
+        for statement_index in 0..=data.statements.len() {
+            cursor.seek_after_primary_effect(Location { block, statement_index });
+            borrowed.union(cursor.get());
+        }
+    }
+    borrowed
+}
+
+/// Finds the locals to split, and returns the used fields of each of them. The
+/// fields of the other locals are empty, and so are those of candidates without
+/// any field uses, which are left to `SimplifyLocals`.
+fn compute_split_fields<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+) -> IndexVec<Local, FxIndexMap<Field, Ty<'tcx>>> {
+    let borrowed = borrowed_locals(tcx, body);
+
+    let mut candidates = BitSet::new_empty(body.local_decls.len());
+    for (local, decl) in body.local_decls.iter_enumerated() {
+        // Arguments and the return place are used as a whole by the caller.
+        if body.local_kind(local) == LocalKind::Arg
+            || body.local_kind(local) == LocalKind::ReturnPointer
+        {
+            continue;
+        }
+        if is_splittable(decl.ty) && !borrowed.contains(local) {
+            candidates.insert(local);
+        }
+    }
+
+    let mut field_uses = FieldUses {
+        fields: IndexVec::from_elem(FxIndexMap::default(), &body.local_decls),
+        candidates,
+        drop_debuginfo: tcx.sess.opts.debuginfo == DebugInfo::None,
+    };
+    field_uses.visit_body(body);
+    let FieldUses { mut fields, candidates, .. } = field_uses;
+
+    for (local, fields) in fields.iter_enumerated_mut() {
+        if !candidates.contains(local) {
+            fields.clear();
+        }
+    }
+    fields
+}
+
+/// Allocates the locals replacing the fields of each local to split.
+fn allocate_replacements<'tcx>(
+    body: &mut Body<'tcx>,
+    split_fields: IndexVec<Local, FxIndexMap<Field, Ty<'tcx>>>,
+) -> Replacements {
+    let mut replacements = IndexVec::from_elem(None, &body.local_decls);
+    for (local, fields) in split_fields.into_iter_enumerated() {
+        if fields.is_empty() {
+            continue;
+        }
+        let source_info = body.local_decls[local].source_info;
+        let fields = fields
+            .into_iter()
+            .map(|(field, ty)| {
+                (field, body.local_decls.push(LocalDecl::with_source_info(ty, source_info)))
+            })
+            .collect();
+        debug!("splitting {:?} into {:?}", local, fields);
+        replacements[local] = Some(fields);
+    }
+    replacements
+}
+
+/// Collects the fields used of each candidate local, and removes the
+/// candidates that are used as a whole.
+struct FieldUses<'tcx> {
+    fields: IndexVec<Local, FxIndexMap<Field, Ty<'tcx>>>,
+    candidates: BitSet<Local>,
+    /// Whether debuginfo referring to a whole candidate can be dropped.
+    drop_debuginfo: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for FieldUses<'tcx> {
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, _: Location) {
+        match context {
+            PlaceContext::NonUse(NonUseContext::StorageLive)
+            | PlaceContext::NonUse(NonUseContext::StorageDead) => {}
+            PlaceContext::NonUse(NonUseContext::VarDebugInfo) if self.drop_debuginfo => {}
+            _ => {
+                self.candidates.remove(local);
+            }
+        }
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if let Some(&ProjectionElem::Field(field, ty)) = place.projection.first() {
+            if self.candidates.contains(place.local) {
+                self.fields[place.local].entry(field).or_insert(ty);
+            }
+            // Only visit the locals used in the projection, like indices.
+            self.visit_projection(place.as_ref(), context, location);
+        } else {
+            self.super_place(place, context, location);
+        }
+    }
+}
+
+fn replace_split_locals<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mut Body<'tcx>,
+    replacements: &Replacements,
+) {
+    // Each storage marker of a split local becomes a storage marker of each
+    // of its replacements.
+    for block in body.basic_blocks_mut() {
+        let has_split_storage_markers = block.statements.iter().any(|statement| {
+            matches!(
+                statement.kind,
+                StatementKind::StorageLive(local) | StatementKind::StorageDead(local)
+                    if replacements[local].is_some()
+            )
+        });
+        if !has_split_storage_markers {
+            continue;
+        }
+
+        for statement in mem::take(&mut block.statements) {
+            let (local, is_live) = match statement.kind {
+                StatementKind::StorageLive(local) => (local, true),
+                StatementKind::StorageDead(local) => (local, false),
+                _ => {
+                    block.statements.push(statement);
+                    continue;
+                }
+            };
+            let fields = match &replacements[local] {
+                Some(fields) => fields,
+                None => {
+                    block.statements.push(statement);
+                    continue;
+                }
+            };
+            for &new_local in fields.values() {
+                let kind = if is_live {
+                    StatementKind::StorageLive(new_local)
+                } else {
+                    StatementKind::StorageDead(new_local)
+                };
+                block.statements.push(Statement { source_info: statement.source_info, kind });
+            }
+        }
+    }
+
+    body.var_debug_info.retain(|var_debug_info| match &var_debug_info.value {
+        VarDebugInfoContents::Place(place) => {
+            replacements[place.local].is_none()
+                || matches!(place.projection.first(), Some(ProjectionElem::Field(..)))
+        }
+        VarDebugInfoContents::Const(_) => true,
+    });
+
+    ReplaceFieldProjections { tcx, replacements }.visit_body(body);
+}
+
+/// Replaces every field projection of a split local by the local replacing
+/// that field.
+struct ReplaceFieldProjections<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    replacements: &'a Replacements,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ReplaceFieldProjections<'_, 'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_place(&mut self, place: &mut Place<'tcx>, context: PlaceContext, location: Location) {
+        if let Some(fields) = &self.replacements[place.local] {
+            if let Some(&ProjectionElem::Field(field, _)) = place.projection.first() {
+                *place = Place {
+                    local: fields[&field],
+                    projection: self.tcx.intern_place_elems(&place.projection[1..]),
+                };
+            }
+        }
+        self.super_place(place, context, location);
+    }
+}
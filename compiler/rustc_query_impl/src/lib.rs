@@ -51,7 +51,7 @@
 use rustc_middle::ty::query::on_disk_cache;
 
 mod profiling_support;
-pub use self::profiling_support::alloc_self_profile_query_strings;
+pub use self::profiling_support::{alloc_self_profile_query_strings, print_self_profile_summary};
 
 rustc_query_append! { [define_queries!][<'tcx>] }
 
@@ -318,3 +318,33 @@ macro_rules! alloc_once {
 
     rustc_query_append! { [alloc_once!][<'tcx>] }
 }
+
+/// Prints the `top_n` queries with the highest self time for
+/// `-Z self-profile-summary`. Like `alloc_self_profile_query_strings`, this
+/// walks all query caches to find out which query each invocation belongs to.
+pub fn print_self_profile_summary(tcx: TyCtxt<'tcx>, top_n: usize) {
+    let summary = match tcx.prof.query_summary() {
+        Some(summary) => summary,
+        None => return,
+    };
+
+    let mut query_names = FxHashMap::default();
+
+    macro_rules! collect_query_names {
+        (<$tcx:tt>
+            $($(#[$attr:meta])* [$($modifiers:tt)*] fn $name:ident($K:ty) -> $V:ty,)*
+        ) => {
+            $({
+                tcx.query_caches.$name.iter_results(|results| {
+                    for (_, _, dep_node_index) in results {
+                        query_names.insert(dep_node_index.as_u32(), stringify!($name));
+                    }
+                });
+            })*
+        }
+    }
+
+    rustc_query_append! { [collect_query_names!][<'tcx>] }
+
+    summary.print(top_n, |query_invocation_id| query_names.get(&query_invocation_id.0).copied());
+}
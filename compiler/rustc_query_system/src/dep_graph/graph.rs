@@ -4,6 +4,7 @@
 use rustc_data_structures::sharded::{self, Sharded};
 use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
 use rustc_data_structures::sync::{AtomicU32, AtomicU64, Lock, LockGuard, Lrc, Ordering};
+use rustc_data_structures::thin_vec::ThinVec;
 use rustc_data_structures::unlikely;
 use rustc_errors::Diagnostic;
 use rustc_index::vec::{Idx, IndexVec};
@@ -80,11 +81,15 @@ struct DepGraphData<K: DepKind> {
 
     colors: DepNodeColorMap,
 
-    /// A set of loaded diagnostics that is in the progress of being emitted.
-    emitting_diagnostics: Mutex<FxHashSet<DepNodeIndex>>,
+    /// The side effects of the tasks executed, or of the nodes marked green,
+    /// during the current compilation session.
+    side_effects: Lock<FxHashMap<DepNodeIndex, QuerySideEffects>>,
 
-    /// Used to wait for diagnostics to be emitted.
-    emitting_diagnostics_cond_var: Condvar,
+    /// A set of loaded side effects that is in the progress of being replayed.
+    emitting_side_effects: Mutex<FxHashSet<DepNodeIndex>>,
+
+    /// Used to wait for side effects to be replayed.
+    emitting_side_effects_cond_var: Condvar,
 
     /// When we load, there may be `.o` files, cached MIR, or other such
     /// things available to us. If we find that they are not dirty, we
@@ -95,6 +100,14 @@ struct DepGraphData<K: DepKind> {
     dep_node_debug: Lock<FxHashMap<DepNode<K>, String>>,
 }
 
+impl<K: DepKind> DepGraphData<K> {
+    #[cold]
+    #[inline(never)]
+    fn store_side_effects(&self, dep_node_index: DepNodeIndex, side_effects: QuerySideEffects) {
+        self.side_effects.lock().entry(dep_node_index).or_default().append(side_effects);
+    }
+}
+
 pub fn hash_result<HashCtxt, R>(hcx: &mut HashCtxt, result: &R) -> Option<Fingerprint>
 where
     R: HashStable<HashCtxt>,
@@ -117,8 +130,9 @@ pub fn new(
                 previous_work_products: prev_work_products,
                 dep_node_debug: Default::default(),
                 current: CurrentDepGraph::new(prev_graph_node_count),
-                emitting_diagnostics: Default::default(),
-                emitting_diagnostics_cond_var: Condvar::new(),
+                side_effects: Default::default(),
+                emitting_side_effects: Default::default(),
+                emitting_side_effects_cond_var: Condvar::new(),
                 previous: prev_graph,
                 colors: DepNodeColorMap::new(prev_graph_node_count),
             })),
@@ -255,6 +269,7 @@ pub fn with_task<Ctxt: HasDepContext<DepKind = K>, A, R>(
                     node: Some(_key),
                     reads: SmallVec::new(),
                     read_set: Default::default(),
+                    side_effects: Default::default(),
                     phantom_data: PhantomData,
                 })
             },
@@ -275,7 +290,13 @@ fn with_task_impl<Ctxt: HasDepContext<DepKind = K>, A, R>(
             let dcx = cx.dep_context();
             let task_deps = create_task(key).map(Lock::new);
             let result = K::with_deps(task_deps.as_ref(), || task(cx, arg));
-            let edges = task_deps.map_or_else(|| smallvec![], |lock| lock.into_inner().reads);
+            let (edges, side_effects) = match task_deps {
+                Some(task_deps) => {
+                    let task_deps = task_deps.into_inner();
+                    (task_deps.reads, task_deps.side_effects)
+                }
+                None => (smallvec![], QuerySideEffects::default()),
+            };
 
             let mut hcx = dcx.create_stable_hashing_context();
             let current_fingerprint = hash_result(&mut hcx, &result);
@@ -356,6 +377,10 @@ fn with_task_impl<Ctxt: HasDepContext<DepKind = K>, A, R>(
                 )
             };
 
+            if unlikely!(!side_effects.is_empty()) {
+                data.store_side_effects(dep_node_index, side_effects);
+            }
+
             (result, dep_node_index)
         } else {
             // Incremental compilation is turned off. We just execute the task
@@ -402,6 +427,12 @@ pub fn with_anon_task<OP, R>(&self, dep_kind: K, op: OP) -> (R, DepNodeIndex)
                 Fingerprint::ZERO,
             );
 
+            // Many anonymous tasks can share the same `DepNode`, so their side
+            // effects are aggregated.
+            if unlikely!(!task_deps.side_effects.is_empty()) {
+                data.store_side_effects(dep_node_index, task_deps.side_effects);
+            }
+
             (result, dep_node_index)
         } else {
             (op(), self.next_virtual_depnode_index())
@@ -466,6 +497,27 @@ pub fn read_index(&self, dep_node_index: DepNodeIndex) {
         }
     }
 
+    /// Records `diagnostic` as a side effect of the current task, so that it is
+    /// emitted again when the node of the task is marked green in the next
+    /// compilation session. Diagnostics emitted outside of any task, or while
+    /// dependency tracking is ignored, are not recorded.
+    #[inline]
+    pub fn record_diagnostic(&self, diagnostic: &Diagnostic) {
+        if self.data.is_some() {
+            K::read_deps(|task_deps| {
+                if let Some(task_deps) = task_deps {
+                    task_deps.lock().side_effects.diagnostics.extend(Some(diagnostic.clone()));
+                }
+            })
+        }
+    }
+
+    /// Access the side effects of the current compilation session, which are
+    /// written to the on-disk cache for use in the next one.
+    pub fn side_effects(&self) -> LockGuard<'_, FxHashMap<DepNodeIndex, QuerySideEffects>> {
+        self.data.as_ref().unwrap().side_effects.lock()
+    }
+
     #[inline]
     pub fn dep_node_index_of(&self, dep_node: &DepNode<K>) -> DepNodeIndex {
         self.dep_node_index_of_opt(dep_node).unwrap()
@@ -781,11 +833,11 @@ fn try_mark_previous_green<Ctxt: QueryContext<DepKind = K>>(
             data.current.intern_dark_green_node(&data.previous, prev_dep_node_index)
         };
 
-        // ... emitting any stored diagnostic ...
+        // ... replaying any stored side effect ...
 
-        // FIXME: Store the fact that a node has diagnostics in a bit in the dep graph somewhere
+        // FIXME: Store the fact that a node has side effects in a bit in the dep graph somewhere
         // Maybe store a list on disk and encode this fact in the DepNodeState
-        let diagnostics = tcx.load_diagnostics(prev_dep_node_index);
+        let side_effects = tcx.load_side_effects(prev_dep_node_index);
 
         #[cfg(not(parallel_compiler))]
         debug_assert!(
@@ -795,8 +847,8 @@ fn try_mark_previous_green<Ctxt: QueryContext<DepKind = K>>(
             dep_node
         );
 
-        if unlikely!(!diagnostics.is_empty()) {
-            self.emit_diagnostics(tcx, data, dep_node_index, prev_dep_node_index, diagnostics);
+        if unlikely!(!side_effects.is_empty()) {
+            self.emit_side_effects(tcx, data, dep_node_index, prev_dep_node_index, side_effects);
         }
 
         // ... and finally storing a "Green" entry in the color map.
@@ -807,53 +859,57 @@ fn try_mark_previous_green<Ctxt: QueryContext<DepKind = K>>(
         Some(dep_node_index)
     }
 
-    /// Atomically emits some loaded diagnostics.
+    /// Atomically replays some loaded side effects.
     /// This may be called concurrently on multiple threads for the same dep node.
     #[cold]
     #[inline(never)]
-    fn emit_diagnostics<Ctxt: QueryContext<DepKind = K>>(
+    fn emit_side_effects<Ctxt: QueryContext<DepKind = K>>(
         &self,
         tcx: Ctxt,
         data: &DepGraphData<K>,
         dep_node_index: DepNodeIndex,
         prev_dep_node_index: SerializedDepNodeIndex,
-        diagnostics: Vec<Diagnostic>,
+        side_effects: QuerySideEffects,
     ) {
-        let mut emitting = data.emitting_diagnostics.lock();
+        let mut emitting = data.emitting_side_effects.lock();
 
         if data.colors.get(prev_dep_node_index) == Some(DepNodeColor::Green(dep_node_index)) {
-            // The node is already green so diagnostics must have been emitted already
+            // The node is already green so side effects must have been replayed already
             return;
         }
 
         if emitting.insert(dep_node_index) {
             // We were the first to insert the node in the set so this thread
-            // must emit the diagnostics and signal other potentially waiting
+            // must replay the side effects and signal other potentially waiting
             // threads after.
             mem::drop(emitting);
 
-            // Promote the previous diagnostics to the current session.
-            tcx.store_diagnostics(dep_node_index, diagnostics.clone().into());
+            // Promote the previous side effects to the current session.
+            data.store_side_effects(dep_node_index, side_effects.clone());
 
             let handle = tcx.dep_context().sess().diagnostic();
 
-            for diagnostic in diagnostics {
-                handle.emit_diagnostic(&diagnostic);
-            }
+            // The diagnostics are side effects of the node marked green, not of
+            // the task that is currently executing, so don't record them again.
+            self.with_ignore(|| {
+                for diagnostic in side_effects.diagnostics.iter() {
+                    handle.emit_diagnostic(diagnostic);
+                }
+            });
 
-            // Mark the node as green now that diagnostics are emitted
+            // Mark the node as green now that side effects are replayed
             data.colors.insert(prev_dep_node_index, DepNodeColor::Green(dep_node_index));
 
             // Remove the node from the set
-            data.emitting_diagnostics.lock().remove(&dep_node_index);
+            data.emitting_side_effects.lock().remove(&dep_node_index);
 
             // Wake up waiters
-            data.emitting_diagnostics_cond_var.notify_all();
+            data.emitting_side_effects_cond_var.notify_all();
         } else {
-            // We must wait for the other thread to finish emitting the diagnostic
+            // We must wait for the other thread to finish replaying the side effects
 
             loop {
-                data.emitting_diagnostics_cond_var.wait(&mut emitting);
+                data.emitting_side_effects_cond_var.wait(&mut emitting);
                 if data.colors.get(prev_dep_node_index) == Some(DepNodeColor::Green(dep_node_index))
                 {
                     break;
@@ -1663,6 +1719,7 @@ pub struct TaskDeps<K> {
     node: Option<DepNode<K>>,
     reads: EdgesVec,
     read_set: FxHashSet<DepNodeIndex>,
+    side_effects: QuerySideEffects,
     phantom_data: PhantomData<DepNode<K>>,
 }
 
@@ -1673,11 +1730,35 @@ fn default() -> Self {
             node: None,
             reads: EdgesVec::new(),
             read_set: FxHashSet::default(),
+            side_effects: QuerySideEffects::default(),
             phantom_data: PhantomData,
         }
     }
 }
 
+/// The side effects of a task, i.e. the changes to global state that would be
+/// lost if the task were not executed. They are stored along with the
+/// dep-graph and replayed when the node of the task is marked green in the
+/// next compilation session, so that reusing its result has the same
+/// observable effects as executing it again.
+#[derive(Clone, Debug, Default, Encodable, Decodable)]
+pub struct QuerySideEffects {
+    /// The diagnostics emitted while executing the task. Diagnostics emitted
+    /// by the tasks it executes in turn are side effects of those tasks.
+    pub diagnostics: ThinVec<Diagnostic>,
+}
+
+impl QuerySideEffects {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn append(&mut self, other: QuerySideEffects) {
+        self.diagnostics.extend(Into::<Vec<_>>::into(other.diagnostics));
+    }
+}
+
 // A data structure that stores Option<DepNodeColor> values as a contiguous
 // array, using one u32 per entry.
 struct DepNodeColorMap {
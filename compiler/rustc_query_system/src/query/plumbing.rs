@@ -16,9 +16,8 @@
 use rustc_data_structures::fingerprint::Fingerprint;
 use rustc_data_structures::fx::{FxHashMap, FxHasher};
 use rustc_data_structures::sharded::{get_shard_index_by_hash, Sharded};
-use rustc_data_structures::sync::{Lock, LockGuard};
-use rustc_data_structures::thin_vec::ThinVec;
-use rustc_errors::{Diagnostic, FatalError};
+use rustc_data_structures::sync::LockGuard;
+use rustc_errors::FatalError;
 use rustc_span::Span;
 use std::collections::hash_map::Entry;
 use std::fmt::Debug;
@@ -320,15 +319,6 @@ fn complete(self, result: C::Value, dep_node_index: DepNodeIndex) -> C::Stored {
     }
 }
 
-fn with_diagnostics<F, R>(f: F) -> (R, ThinVec<Diagnostic>)
-where
-    F: FnOnce(Option<&Lock<ThinVec<Diagnostic>>>) -> R,
-{
-    let diagnostics = Lock::new(ThinVec::new());
-    let result = f(Some(&diagnostics));
-    (result, diagnostics.into_inner())
-}
-
 impl<'tcx, D, C> Drop for JobOwner<'tcx, D, C>
 where
     D: Copy + Clone + Eq + Hash,
@@ -447,32 +437,24 @@ fn try_execute_query<CTX, C>(
     if query.anon {
         let prof_timer = tcx.dep_context().profiler().query_provider();
 
-        let ((result, dep_node_index), diagnostics) = with_diagnostics(|diagnostics| {
-            tcx.start_query(job.id, diagnostics, || {
-                tcx.dep_context()
-                    .dep_graph()
-                    .with_anon_task(query.dep_kind, || query.compute(tcx, key))
-            })
+        let (result, dep_node_index) = tcx.start_query(job.id, || {
+            tcx.dep_context().dep_graph().with_anon_task(query.dep_kind, || query.compute(tcx, key))
         });
 
         prof_timer.finish_with_query_invocation_id(dep_node_index.into());
 
         tcx.dep_context().dep_graph().read_index(dep_node_index);
 
-        if unlikely!(!diagnostics.is_empty()) {
-            tcx.store_diagnostics_for_anon_node(dep_node_index, diagnostics);
-        }
-
         return job.complete(result, dep_node_index);
     }
 
     let dep_node = query.to_dep_node(*tcx.dep_context(), &key);
 
     if !query.eval_always {
-        // The diagnostics for this query will be
+        // The side effects of this query will be
         // promoted to the current session during
-        // `try_mark_green()`, so we can ignore them here.
-        let loaded = tcx.start_query(job.id, None, || {
+        // `try_mark_green()`.
+        let loaded = tcx.start_query(job.id, || {
             let marked = tcx.dep_context().dep_graph().try_mark_green_and_read(tcx, &dep_node);
             marked.map(|(prev_dep_node_index, dep_node_index)| {
                 (
@@ -613,34 +595,30 @@ fn force_query_with_job<C, CTX>(
 
     let prof_timer = tcx.dep_context().profiler().query_provider();
 
-    let ((result, dep_node_index), diagnostics) = with_diagnostics(|diagnostics| {
-        tcx.start_query(job.id, diagnostics, || {
-            if query.eval_always {
-                tcx.dep_context().dep_graph().with_eval_always_task(
-                    dep_node,
-                    tcx,
-                    key,
-                    query.compute,
-                    query.hash_result,
-                )
-            } else {
-                tcx.dep_context().dep_graph().with_task(
-                    dep_node,
-                    tcx,
-                    key,
-                    query.compute,
-                    query.hash_result,
-                )
-            }
-        })
+    // The diagnostics emitted by the query are recorded as side effects of
+    // its task by the dep-graph.
+    let (result, dep_node_index) = tcx.start_query(job.id, || {
+        if query.eval_always {
+            tcx.dep_context().dep_graph().with_eval_always_task(
+                dep_node,
+                tcx,
+                key,
+                query.compute,
+                query.hash_result,
+            )
+        } else {
+            tcx.dep_context().dep_graph().with_task(
+                dep_node,
+                tcx,
+                key,
+                query.compute,
+                query.hash_result,
+            )
+        }
     });
 
     prof_timer.finish_with_query_invocation_id(dep_node_index.into());
 
-    if unlikely!(!diagnostics.is_empty()) && dep_node.kind != DepKind::NULL {
-        tcx.store_diagnostics(dep_node_index, diagnostics);
-    }
-
     let result = job.complete(result, dep_node_index);
 
     (result, dep_node_index)
@@ -184,6 +184,25 @@ pub enum MirSpanview {
     Block,
 }
 
+/// The format of the error code explanations printed by `--explain` and
+/// `--print error-codes`.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum ExplainFormat {
+    /// The Markdown of the explanation, for humans.
+    Human,
+    /// JSON objects with the parts of the explanation, for tools.
+    Json,
+}
+
+/// The different settings that the `-Z apply-suggestions` flag can have.
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum ApplySuggestions {
+    /// Default `-Z apply-suggestions` or `-Z apply-suggestions=in-place`: rewrite the source files.
+    InPlace,
+    /// `-Z apply-suggestions=diff`: print the changes as a unified diff instead.
+    Diff,
+}
+
 #[derive(Clone, PartialEq, Hash)]
 pub enum LinkerPluginLto {
     LinkerPlugin(PathBuf),
@@ -326,6 +345,8 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF 2.1.0 log for the whole session, consumed by code scanning tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -534,6 +555,9 @@ pub enum PrintRequest {
     TlsModels,
     TargetSpec,
     NativeStaticLibs,
+    LintLevels,
+    LintLevelsJson,
+    ErrorCodes,
 }
 
 #[derive(Copy, Clone)]
@@ -734,7 +758,10 @@ fn default() -> Options {
             remap_path_prefix: Vec::new(),
             edition: DEFAULT_EDITION,
             json_artifact_notifications: false,
+            json_unused_externs: false,
+            explain_format: ExplainFormat::Human,
             pretty: None,
+            early_warnings: Vec::new(),
         }
     }
 }
@@ -784,6 +811,9 @@ pub fn diagnostic_handler_flags(&self, can_emit_warnings: bool) -> HandlerFlags
             report_delayed_bugs: self.report_delayed_bugs,
             macro_backtrace: self.macro_backtrace,
             deduplicate_diagnostics: self.deduplicate_diagnostics,
+            collect_machine_applicable_suggestions: self.apply_suggestions.is_some(),
+            error_limit_per_file: self.error_limit_per_file,
+            error_limit_per_code: self.error_limit_per_code,
         }
     }
 
@@ -1097,7 +1127,8 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "Compiler information to print on stdout",
             "[crate-name|file-names|sysroot|target-libdir|cfg|target-list|\
              target-cpus|target-features|relocation-models|\
-             code-models|tls-models|target-spec-json|native-static-libs]",
+             code-models|tls-models|target-spec-json|native-static-libs|\
+             lint-levels|lint-levels-json|error-codes]",
         ),
         opt::flagmulti_s("g", "", "Equivalent to -C debuginfo=2"),
         opt::flagmulti_s("O", "", "Equivalent to -C opt-level=2"),
@@ -1163,6 +1194,12 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "human|json|short",
         ),
         opt::multi_s("", "json", "Configure the JSON output of the compiler", "CONFIG"),
+        opt::opt(
+            "",
+            "explain-format",
+            "How the explanations of `--explain` and `--print error-codes` are printed",
+            "human|json",
+        ),
         opt::opt_s(
             "",
             "color",
@@ -1252,13 +1289,15 @@ pub fn parse_color(matches: &getopts::Matches) -> ColorConfig {
 
 /// Parse the `--json` flag.
 ///
-/// The first value returned is how to render JSON diagnostics, and the second
-/// is whether or not artifact notifications are enabled.
-pub fn parse_json(matches: &getopts::Matches) -> (HumanReadableErrorType, bool) {
+/// The first value returned is how to render JSON diagnostics, the second
+/// is whether or not artifact notifications are enabled, and the third is
+/// whether or not unused `--extern` crates are reported.
+pub fn parse_json(matches: &getopts::Matches) -> (HumanReadableErrorType, bool, bool) {
     let mut json_rendered: fn(ColorConfig) -> HumanReadableErrorType =
         HumanReadableErrorType::Default;
     let mut json_color = ColorConfig::Never;
     let mut json_artifact_notifications = false;
+    let mut json_unused_externs = false;
     for option in matches.opt_strs("json") {
         // For now conservatively forbid `--color` with `--json` since `--json`
         // won't actually be emitting any colors and anything colorized is
@@ -1275,6 +1314,7 @@ pub fn parse_json(matches: &getopts::Matches) -> (HumanReadableErrorType, bool)
                 "diagnostic-short" => json_rendered = HumanReadableErrorType::Short,
                 "diagnostic-rendered-ansi" => json_color = ColorConfig::Always,
                 "artifacts" => json_artifact_notifications = true,
+                "unused-externs" => json_unused_externs = true,
                 s => early_error(
                     ErrorOutputType::default(),
                     &format!("unknown `--json` option `{}`", s),
@@ -1282,7 +1322,7 @@ pub fn parse_json(matches: &getopts::Matches) -> (HumanReadableErrorType, bool)
             }
         }
     }
-    (json_rendered(json_color), json_artifact_notifications)
+    (json_rendered(json_color), json_artifact_notifications, json_unused_externs)
 }
 
 /// Parses the `--error-format` flag.
@@ -1305,6 +1345,7 @@ pub fn parse_error_format(
             }
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
+            Some("sarif") => ErrorOutputType::Sarif,
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
 
             Some(arg) => early_error(
@@ -1387,6 +1428,12 @@ fn check_debug_option_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                "`--error-format=sarif` is unstable",
+            );
+        }
     }
 }
 
@@ -1423,10 +1470,20 @@ fn parse_output_types(
     OutputTypes(output_types)
 }
 
+/// Emits a warning about the command line, or buffers it in `early_warnings`
+/// with `--error-format=sarif`, see `Options::early_warnings`.
+fn warn_early(error_format: ErrorOutputType, early_warnings: &mut Vec<String>, msg: &str) {
+    match error_format {
+        ErrorOutputType::Sarif => early_warnings.push(msg.to_owned()),
+        _ => early_warn(error_format, msg),
+    }
+}
+
 fn should_override_cgus_and_disable_thinlto(
     output_types: &OutputTypes,
     matches: &getopts::Matches,
     error_format: ErrorOutputType,
+    early_warnings: &mut Vec<String>,
     mut codegen_units: Option<usize>,
 ) -> (bool, Option<usize>) {
     let mut disable_thinlto = false;
@@ -1444,8 +1501,9 @@ fn should_override_cgus_and_disable_thinlto(
             Some(n) if n > 1 => {
                 if matches.opt_present("o") {
                     for ot in &incompatible {
-                        early_warn(
+                        warn_early(
                             error_format,
+                            early_warnings,
                             &format!(
                                 "`--emit={}` with `-o` incompatible with \
                                  `-C codegen-units=N` for N > 1",
@@ -1453,7 +1511,11 @@ fn should_override_cgus_and_disable_thinlto(
                             ),
                         );
                     }
-                    early_warn(error_format, "resetting to default -C codegen-units=1");
+                    warn_early(
+                        error_format,
+                        early_warnings,
+                        "resetting to default -C codegen-units=1",
+                    );
                     codegen_units = Some(1);
                     disable_thinlto = true;
                 }
@@ -1522,6 +1584,17 @@ fn collect_print_requests(
                 );
             }
         }
+        "lint-levels" if dopts.unstable_options => PrintRequest::LintLevels,
+        "lint-levels-json" if dopts.unstable_options => PrintRequest::LintLevelsJson,
+        "error-codes" if dopts.unstable_options => PrintRequest::ErrorCodes,
+        "lint-levels" | "lint-levels-json" | "error-codes" => early_error(
+            error_format,
+            &format!(
+                "the `-Z unstable-options` flag must also be passed to \
+                 enable the {} print option",
+                s
+            ),
+        ),
         req => early_error(error_format, &format!("unknown print request `{}`", req)),
     }));
 
@@ -1860,10 +1933,12 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
 
     let edition = parse_crate_edition(matches);
 
-    let (json_rendered, json_artifact_notifications) = parse_json(matches);
+    let (json_rendered, json_artifact_notifications, json_unused_externs) = parse_json(matches);
 
     let error_format = parse_error_format(matches, color, json_rendered);
 
+    let explain_format = parse_explain_format(matches, error_format);
+
     let unparsed_crate_types = matches.opt_strs("crate-type");
     let crate_types = parse_crate_types_from_list(unparsed_crate_types)
         .unwrap_or_else(|e| early_error(error_format, &e[..]));
@@ -1873,13 +1948,23 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
     let mut debugging_opts = build_debugging_options(matches, error_format);
     check_debug_option_stability(&debugging_opts, error_format, json_rendered);
 
+    if !debugging_opts.unstable_options && json_unused_externs {
+        early_error(
+            error_format,
+            "the `-Z unstable-options` flag must also be passed to \
+             enable the flag `--json=unused-externs`",
+        );
+    }
+
     let output_types = parse_output_types(&debugging_opts, matches, error_format);
 
+    let mut early_warnings = Vec::new();
     let mut cg = build_codegen_options(matches, error_format);
     let (disable_thinlto, mut codegen_units) = should_override_cgus_and_disable_thinlto(
         &output_types,
         matches,
         error_format,
+        &mut early_warnings,
         cg.codegen_units,
     );
 
@@ -1929,8 +2014,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
                 debugging_opts.symbol_mangling_version = Some(SymbolManglingVersion::V0);
             }
             Some(SymbolManglingVersion::Legacy) => {
-                early_warn(
+                warn_early(
                     error_format,
+                    &mut early_warnings,
                     "-Z instrument-coverage requires symbol mangling version `v0`, \
                     but `-Z symbol-mangling-version=legacy` was specified",
                 );
@@ -1946,8 +2032,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
                 // function. Inlining after these counters are injected can produce duplicate counters,
                 // resulting in an invalid coverage map (and ICE); so this option combination is not
                 // allowed.
-                early_warn(
+                warn_early(
                     error_format,
+                    &mut early_warnings,
                     &format!(
                         "`-Z mir-opt-level={}` (or any level > 1) enables function inlining, which \
                     is incompatible with `-Z instrument-coverage`. Inlining will be disabled.",
@@ -1997,7 +2084,11 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
     let borrowck_mode = parse_borrowck_mode(&debugging_opts, error_format);
 
     if !cg.remark.is_empty() && debuginfo == DebugInfo::None {
-        early_warn(error_format, "-C remark requires \"-C debuginfo=n\" to show source locations");
+        warn_early(
+            error_format,
+            &mut early_warnings,
+            "-C remark requires \"-C debuginfo=n\" to show source locations",
+        );
     }
 
     let externs = parse_externs(matches, &debugging_opts, error_format);
@@ -2050,7 +2141,27 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         remap_path_prefix,
         edition,
         json_artifact_notifications,
+        json_unused_externs,
+        explain_format,
         pretty,
+        early_warnings,
+    }
+}
+
+fn parse_explain_format(
+    matches: &getopts::Matches,
+    error_format: ErrorOutputType,
+) -> ExplainFormat {
+    match matches.opt_str("explain-format").as_deref() {
+        None | Some("human") => ExplainFormat::Human,
+        Some("json") => ExplainFormat::Json,
+        Some(arg) => early_error(
+            error_format,
+            &format!(
+                "argument for `--explain-format` must be `human` or `json` (instead was `{}`)",
+                arg
+            ),
+        ),
     }
 }
 
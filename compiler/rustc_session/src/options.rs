@@ -147,7 +147,20 @@ pub struct Options {
         // by the compiler.
         json_artifact_notifications: bool [TRACKED],
 
+        // `true` if we're emitting a JSON record for each `--extern` crate
+        // that the crate doesn't use.
+        json_unused_externs: bool [UNTRACKED],
+
+        // The format of the error code explanations of `--explain` and
+        // `--print error-codes`.
+        explain_format: ExplainFormat [UNTRACKED],
+
         pretty: Option<PpMode> [UNTRACKED],
+
+        // Warnings about the command line that are emitted once the session
+        // exists. This is only used with `--error-format=sarif`, where they
+        // are part of the single log written for the session.
+        early_warnings: Vec<String> [UNTRACKED],
     }
 );
 
@@ -262,6 +275,7 @@ mod $mod_desc {
         pub const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavor::one_of();
         pub const parse_optimization_fuel: &str = "crate=integer";
         pub const parse_mir_spanview: &str = "`statement` (default), `terminator`, or `block`";
+        pub const parse_apply_suggestions: &str = "`in-place` (default) or `diff`";
         pub const parse_unpretty: &str = "`string` or `string=string`";
         pub const parse_treat_err_as_bug: &str = "either no value or a number bigger than 0";
         pub const parse_lto: &str =
@@ -592,6 +606,15 @@ fn parse_mir_spanview(slot: &mut Option<MirSpanview>, v: Option<&str>) -> bool {
             true
         }
 
+        fn parse_apply_suggestions(slot: &mut Option<ApplySuggestions>, v: Option<&str>) -> bool {
+            *slot = Some(match v {
+                None | Some("in-place") => ApplySuggestions::InPlace,
+                Some("diff") => ApplySuggestions::Diff,
+                _ => return false,
+            });
+            true
+        }
+
         fn parse_treat_err_as_bug(slot: &mut Option<NonZeroUsize>, v: Option<&str>) -> bool {
             match v {
                 Some(s) => { *slot = s.parse().ok(); slot.is_some() }
@@ -857,6 +880,9 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         "only allow the listed language features to be enabled in code (space separated)"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata (default: no)"),
+    apply_suggestions: Option<ApplySuggestions> = (None, parse_apply_suggestions, [UNTRACKED],
+        "apply the machine-applicable suggestions of the emitted diagnostics to the source files \
+        (`in-place`), or print them as a unified diff (`diff`)"),
     assume_incomplete_release: bool = (false, parse_bool, [TRACKED],
         "make cfg(version) treat the current version as incomplete (default: no)"),
     asm_comments: bool = (false, parse_bool, [TRACKED],
@@ -928,6 +954,16 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         "emits a future-incompatibility report for lints (RFC 2834)"),
     emit_stack_sizes: bool = (false, parse_bool, [UNTRACKED],
         "emit a section containing stack size metadata (default: no)"),
+    error_limit_per_code: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "emit at most this many errors and warnings for each error code or lint, and summarize \
+        the others at the end (default: no limit)"),
+    error_limit_per_file: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "emit at most this many errors and warnings for each source file, and summarize the \
+        others at the end (default: no limit)"),
+    explain_borrowck: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "dump the region constraint graph of the functions whose path contains this string, \
+        with the constraints blamed by borrowck diagnostics highlighted, into `-Z dump-mir-dir` \
+        as graphviz and JSON (default: no)"),
     fewer_names: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "reduce memory use by retaining fewer names within compilation artifacts (LLVM-IR) \
         (default: no)"),
@@ -963,6 +999,9 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         "a default MIR inlining threshold (default: 50)"),
     inline_mir_hint_threshold: Option<usize> = (None, parse_opt_uint, [TRACKED],
         "inlining threshold for functions with inline hint (default: 100)"),
+    inline_mir_report: bool = (false, parse_bool, [UNTRACKED],
+        "print a note for each callsite the MIR inliner considers, with the cost of the callee, \
+        the threshold it is compared against and why it was not inlined (default: no)"),
     inline_in_all_cgus: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "control whether `#[inline]` functions are in all CGUs"),
     input_stats: bool = (false, parse_bool, [UNTRACKED],
@@ -989,6 +1028,9 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         "generate JSON tracing data file from LLVM data (default: no)"),
     ls: bool = (false, parse_bool, [UNTRACKED],
         "list the symbols defined by a library crate (default: no)"),
+    location_sensitive_borrowck: bool = (false, parse_bool, [TRACKED],
+        "compute where loans are live with a location-sensitive analysis, like Polonius, \
+        instead of from the regions inferred by NLL (default: no)"),
     macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
         "show macro backtraces (default: no)"),
     merge_functions: Option<MergeFunctions> = (None, parse_merge_functions, [TRACKED],
@@ -1001,6 +1043,10 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         (default: no)"),
     mir_opt_level: Option<usize> = (None, parse_opt_uint, [TRACKED],
         "MIR optimization level (0-4; default: 1 in non optimized builds and 2 in optimized builds)"),
+    mono_items_report: bool = (false, parse_bool, [UNTRACKED],
+        "write the instantiations of each generic function, with their type arguments, MIR \
+        statement count and codegen units, to a `.mono_items.json` file next to the output \
+        (default: no)"),
     mutable_noalias: bool = (false, parse_bool, [TRACKED],
         "emit noalias metadata for mutable references (default: no)"),
     new_llvm_pass_manager: bool = (false, parse_bool, [TRACKED],
@@ -1099,6 +1145,9 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         for example: `-Z self-profile-events=default,query-keys`
         all options: none, all, default, generic-activity, query-provider, query-cache-hit
                      query-blocked, incr-cache-load, query-keys, function-args, args, llvm"),
+    self_profile_summary: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "print the N queries with the highest self time at the end of the session, \
+        along with their number of executions, cache hits and incremental loading time"),
     share_generics: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "make the current crate share its generic instantiations"),
     show_span: Option<String> = (None, parse_opt_string, [TRACKED],
@@ -1110,6 +1159,12 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         "exclude spans when debug-printing compiler state (default: no)"),
     src_hash_algorithm: Option<SourceFileHashAlgorithm> = (None, parse_src_file_hash, [TRACKED],
         "hash algorithm of source files in debug info (`md5`, `sha1`, or `sha256`)"),
+    stack_usage_limit: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "lint functions whose stack usage, estimated from MIR, may exceed this many bytes \
+        (default: no limit)"),
+    stack_usage_report: bool = (false, parse_bool, [UNTRACKED],
+        "write the stack usage of each function, estimated from MIR, to a `.stack_usage.json` \
+        file next to the output (default: no)"),
     strip: Strip = (Strip::None, parse_strip, [UNTRACKED],
         "tell the linker which information to strip (`none` (default), `debuginfo` or `symbols`)"),
     split_dwarf_inlining: bool = (true, parse_bool, [UNTRACKED],
@@ -1138,6 +1193,9 @@ fn parse_split_debuginfo(slot: &mut Option<SplitDebuginfo>, v: Option<&str>) ->
         "measure time of each LLVM pass (default: no)"),
     time_passes: bool = (false, parse_bool, [UNTRACKED],
         "measure time of each rustc pass (default: no)"),
+    time_passes_json: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the time and memory usage of each rustc pass, nested by the pass it \
+        is part of, to the given file as JSON"),
     tls_model: Option<TlsModel> = (None, parse_tls_model, [TRACKED],
         "choose the TLS model to use (`rustc --print tls-models` for details)"),
     trace_macros: bool = (false, parse_bool, [UNTRACKED],
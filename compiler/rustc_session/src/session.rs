@@ -12,14 +12,18 @@
 use rustc_data_structures::flock;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::jobserver::{self, Client};
-use rustc_data_structures::profiling::{duration_to_secs_str, SelfProfiler, SelfProfilerRef};
+use rustc_data_structures::profiling::{
+    duration_to_secs_str, QuerySummary, SelfProfiler, SelfProfilerRef, TimePassesRecorder,
+};
 use rustc_data_structures::sync::{
     self, AtomicU64, AtomicUsize, Lock, Lrc, OnceCell, OneThread, Ordering, Ordering::SeqCst,
 };
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitterWriter;
+use rustc_errors::apply_suggestions;
 use rustc_errors::emitter::{Emitter, EmitterWriter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{Applicability, Diagnostic, DiagnosticBuilder, DiagnosticId, ErrorReported};
 use rustc_lint_defs::FutureBreakage;
 pub use rustc_span::crate_disambiguator::CrateDisambiguator;
@@ -33,6 +37,7 @@
 use std::cell::{self, RefCell};
 use std::env;
 use std::fmt;
+use std::fs;
 use std::io::Write;
 use std::num::NonZeroU32;
 use std::ops::{Div, Mul};
@@ -304,10 +309,86 @@ fn check_miri_unleashed_features(&self) {
     /// Invoked all the way at the end to finish off diagnostics printing.
     pub fn finish_diagnostics(&self, registry: &Registry) {
         self.check_miri_unleashed_features();
+        self.apply_suggestions();
         self.diagnostic().print_error_count(registry);
         self.emit_future_breakage();
     }
 
+    /// Writes the time and memory usage of the passes run so far to the file
+    /// given to `-Z time-passes-json`, if any.
+    pub fn write_time_passes_json(&self) {
+        if let Err(e) = self.prof.write_time_passes_json() {
+            let path = self.opts.debugging_opts.time_passes_json.as_ref().unwrap();
+            self.err(&format!("failed to write `{}`: {}", path.display(), e));
+        }
+    }
+
+    /// Applies the machine-applicable suggestions of the emitted diagnostics
+    /// to the source files, or prints them as a diff (see `-Z apply-suggestions`).
+    fn apply_suggestions(&self) {
+        let mode = match self.opts.debugging_opts.apply_suggestions {
+            Some(mode) => mode,
+            None => return,
+        };
+
+        // Suggestions of stashed diagnostics are only collected once they are emitted.
+        self.diagnostic().emit_stashed_diagnostics();
+        let suggestions = self.diagnostic().take_machine_applicable_suggestions();
+
+        let mut diff = String::new();
+        let mut dropped = 0;
+        for file in apply_suggestions::collect_file_edits(self.source_map(), &suggestions) {
+            let src = match fs::read_to_string(&file.path) {
+                Ok(src) => src,
+                Err(e) => {
+                    self.err(&format!(
+                        "failed to read `{}` to apply suggestions: {}",
+                        file.path.display(),
+                        e
+                    ));
+                    continue;
+                }
+            };
+            if !file.src_hash.matches(&src) {
+                self.warn(&format!(
+                    "not applying suggestions to `{}` because it changed during compilation",
+                    file.path.display()
+                ));
+                continue;
+            }
+
+            let (edits, file_dropped) = apply_suggestions::resolve_overlaps(file.suggestions);
+            dropped += file_dropped;
+            match mode {
+                config::ApplySuggestions::InPlace => {
+                    let new_src = apply_suggestions::apply_edits(&src, &edits);
+                    if let Err(e) = fs::write(&file.path, new_src) {
+                        self.err(&format!(
+                            "failed to apply suggestions to `{}`: {}",
+                            file.path.display(),
+                            e
+                        ));
+                    }
+                }
+                config::ApplySuggestions::Diff => {
+                    diff.push_str(&apply_suggestions::unified_diff(&file.path, &src, &edits));
+                }
+            }
+        }
+
+        if !diff.is_empty() {
+            print!("{}", diff);
+        }
+        match dropped {
+            0 => {}
+            1 => self.warn("1 suggestion was not applied because it overlaps another suggestion"),
+            _ => self.warn(&format!(
+                "{} suggestions were not applied because they overlap other suggestions",
+                dropped
+            )),
+        }
+    }
+
     fn emit_future_breakage(&self) {
         if !self.opts.debugging_opts.emit_future_incompat_report {
             return;
@@ -1272,6 +1353,12 @@ fn default_emitter(
             )
             .ui_testing(sopts.debugging_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif, None) => {
+            Box::new(SarifEmitter::stderr(Some(registry), source_map))
+        }
+        (config::ErrorOutputType::Sarif, Some(dst)) => {
+            Box::new(SarifEmitter::new(dst, Some(registry), source_map))
+        }
     }
 }
 
@@ -1332,6 +1419,9 @@ pub fn build_session(
         emitter,
         sopts.debugging_opts.diagnostic_handler_flags(can_emit_warnings),
     );
+    for msg in &sopts.early_warnings {
+        span_diagnostic.warn(msg);
+    }
 
     let self_profiler = if let SwitchWithOptPath::Enabled(ref d) = sopts.debugging_opts.self_profile
     {
@@ -1346,7 +1436,7 @@ pub fn build_session(
         match profiler {
             Ok(profiler) => Some(Arc::new(profiler)),
             Err(e) => {
-                early_warn(sopts.error_format, &format!("failed to create profiler: {}", e));
+                span_diagnostic.warn(&format!("failed to create profiler: {}", e));
                 None
             }
         }
@@ -1394,10 +1484,19 @@ pub fn build_session(
         CguReuseTracker::new_disabled()
     };
 
+    let time_passes_recorder = sopts
+        .debugging_opts
+        .time_passes_json
+        .clone()
+        .map(|path| Arc::new(TimePassesRecorder::new(path)));
+    let query_summary =
+        sopts.debugging_opts.self_profile_summary.map(|_| Arc::new(QuerySummary::default()));
     let prof = SelfProfilerRef::new(
         self_profiler,
         sopts.debugging_opts.time_passes || sopts.debugging_opts.time,
         sopts.debugging_opts.time_passes,
+        time_passes_recorder,
+        query_summary,
     );
 
     let ctfe_backtrace = Lock::new(match env::var("RUSTC_CTFE_BACKTRACE") {
@@ -1631,6 +1730,7 @@ pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, None, false))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = rustc_errors::Handler::with_emitter(true, None, emitter);
     handler.struct_fatal(msg).emit();
@@ -1646,6 +1746,10 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, None, false))
         }
+        // This writes a SARIF log of its own. Warnings found while parsing the
+        // command line are added to the log of the session instead, see
+        // `config::Options::early_warnings`.
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
     };
     let handler = rustc_errors::Handler::with_emitter(true, None, emitter);
     handler.struct_warn(msg).emit();
@@ -3,4 +3,6 @@
 #![stable(feature = "raw_ext", since = "1.1.0")]
 
 pub mod fs;
+#[cfg(any(target_os = "linux", doc))]
+pub mod process;
 pub mod raw;
@@ -206,6 +206,12 @@ fn as_inner(&self) -> &imp::Process {
     }
 }
 
+impl AsInnerMut<imp::Process> for Child {
+    fn as_inner_mut(&mut self) -> &mut imp::Process {
+        &mut self.handle
+    }
+}
+
 impl FromInner<(imp::Process, imp::StdioPipes)> for Child {
     fn from_inner((handle, io): (imp::Process, imp::StdioPipes)) -> Child {
         Child {
@@ -217,6 +223,8 @@ fn from_inner((handle, io): (imp::Process, imp::StdioPipes)) -> Child {
     }
 }
 
+impl crate::sealed::Sealed for Child {}
+
 impl IntoInner<imp::Process> for Child {
     fn into_inner(self) -> imp::Process {
         self.handle
@@ -181,6 +181,49 @@ fn test_rwlock_try_write() {
     drop(read_guard);
 }
 
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn test_rwlock_writer_preference() {
+    // A waiting writer keeps new readers out, so that a steady stream of
+    // readers can't starve it.
+    let lock = Arc::new(RwLock::new(0));
+    let read_guard = lock.read().unwrap();
+
+    let lock2 = lock.clone();
+    let writer = thread::spawn(move || *lock2.write().unwrap() += 1);
+
+    // New readers get the lock until the writer starts waiting.
+    while lock.try_read().is_ok() {
+        thread::yield_now();
+    }
+
+    drop(read_guard);
+    writer.join().unwrap();
+    assert_eq!(*lock.read().unwrap(), 1);
+}
+
+#[test]
+fn test_rwlock_readers_wait_for_writer() {
+    const N: usize = 10;
+
+    let lock = Arc::new(RwLock::new(0));
+    let mut write_guard = lock.write().unwrap();
+
+    let (tx, rx) = channel();
+    for _ in 0..N {
+        let lock = lock.clone();
+        let tx = tx.clone();
+        thread::spawn(move || tx.send(*lock.read().unwrap()).unwrap());
+    }
+    drop(tx);
+
+    *write_guard = 1;
+    drop(write_guard);
+    for _ in 0..N {
+        assert_eq!(rx.recv().unwrap(), 1);
+    }
+}
+
 #[test]
 fn test_into_inner() {
     let m = RwLock::new(NonCopy(10));
@@ -1,6 +1,8 @@
 #![cfg_attr(test, allow(dead_code))] // why is this necessary?
 use crate::ffi::CStr;
 use crate::io;
+use crate::num::NonZeroUsize;
+use crate::sys_common::thread::unknown_parallelism;
 use crate::time::Duration;
 
 use super::abi::usercalls;
@@ -82,6 +84,10 @@ pub fn join(self) {
     }
 }
 
+pub fn available_parallelism() -> io::Result<NonZeroUsize> {
+    Err(unknown_parallelism())
+}
+
 pub mod guard {
     pub type Guard = !;
     pub unsafe fn current() -> Option<Guard> {
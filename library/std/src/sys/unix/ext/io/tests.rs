@@ -0,0 +1,43 @@
+use super::*;
+use crate::io::{Read, Write};
+use crate::mem::size_of;
+use crate::sys_common::io::test::tmpdir;
+
+#[test]
+fn owned_fd_niche() {
+    assert_eq!(size_of::<Option<OwnedFd>>(), size_of::<RawFd>());
+    assert_eq!(size_of::<Option<BorrowedFd<'static>>>(), size_of::<RawFd>());
+}
+
+#[test]
+fn file_owned_fd_round_trip() {
+    let tmpdir = tmpdir();
+    let path = tmpdir.join("file");
+    let file = fs::File::create(&path).unwrap();
+    let raw_fd = file.as_raw_fd();
+    assert_eq!(file.as_fd().as_raw_fd(), raw_fd);
+
+    let owned = OwnedFd::from(file);
+    assert_eq!(owned.as_raw_fd(), raw_fd);
+    let mut file = fs::File::from(owned);
+    assert_eq!(file.as_raw_fd(), raw_fd);
+    file.write_all(b"hello").unwrap();
+    drop(file);
+
+    let mut contents = String::new();
+    fs::File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "hello");
+}
+
+#[test]
+fn owned_fd_try_clone() {
+    let tmpdir = tmpdir();
+    let owned = OwnedFd::from(fs::File::create(tmpdir.join("file")).unwrap());
+    let cloned = owned.try_clone().unwrap();
+    assert_ne!(cloned.as_raw_fd(), owned.as_raw_fd());
+
+    // Both share the same file description, so writes through either end up
+    // in the same file, and dropping one doesn't close the other.
+    drop(owned);
+    fs::File::from(cloned).write_all(b"still open").unwrap();
+}
@@ -1,4 +1,4 @@
-use crate::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use crate::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
 use crate::sys_common::{self, AsInner, FromInner, IntoInner};
 use crate::{net, sys};
 
@@ -38,3 +38,37 @@ fn into_raw_fd(self) -> RawFd {
     )*};
 }
 impl_into_raw_fd! { TcpStream TcpListener UdpSocket }
+
+macro_rules! impl_as_fd {
+    ($($t:ident)*) => {$(
+        #[unstable(feature = "io_safety", issue = "87074")]
+        impl AsFd for net::$t {
+            #[inline]
+            fn as_fd(&self) -> BorrowedFd<'_> {
+                unsafe { BorrowedFd::borrow_raw_fd(self.as_raw_fd()) }
+            }
+        }
+    )*};
+}
+impl_as_fd! { TcpStream TcpListener UdpSocket }
+
+macro_rules! impl_owned_fd_conversions {
+    ($($t:ident)*) => {$(
+        #[unstable(feature = "io_safety", issue = "87074")]
+        impl From<net::$t> for OwnedFd {
+            #[inline]
+            fn from(socket: net::$t) -> OwnedFd {
+                unsafe { OwnedFd::from_raw_fd(socket.into_raw_fd()) }
+            }
+        }
+
+        #[unstable(feature = "io_safety", issue = "87074")]
+        impl From<OwnedFd> for net::$t {
+            #[inline]
+            fn from(owned: OwnedFd) -> Self {
+                unsafe { Self::from_raw_fd(owned.into_raw_fd()) }
+            }
+        }
+    )*};
+}
+impl_owned_fd_conversions! { TcpStream TcpListener UdpSocket }
@@ -6,12 +6,21 @@
 use crate::sys::cvt;
 use crate::sys::process::process_common::*;
 
+#[cfg(target_os = "linux")]
+use crate::os::linux::process::PidFd;
+#[cfg(target_os = "linux")]
+use crate::os::unix::io::AsRawFd;
+
 #[cfg(target_os = "vxworks")]
 use libc::RTP_ID as pid_t;
 
 #[cfg(not(target_os = "vxworks"))]
 use libc::{c_int, gid_t, pid_t, uid_t};
 
+// The `idtype_t` that makes `waitid` wait on a pidfd (Linux 5.4 and later).
+#[cfg(target_os = "linux")]
+const P_PIDFD: libc::idtype_t = 3;
+
 ////////////////////////////////////////////////////////////////////////////////
 // Command
 ////////////////////////////////////////////////////////////////////////////////
@@ -46,13 +55,13 @@ pub fn spawn(
         // Note that as soon as we're done with the fork there's no need to hold
         // a lock any more because the parent won't do anything and the child is
         // in its own process.
-        let result = unsafe {
+        let (pid, pidfd) = unsafe {
             let _env_lock = sys::os::env_lock();
-            cvt(libc::fork())?
+            self.do_fork()?
         };
 
         let pid = unsafe {
-            match result {
+            match pid {
                 0 => {
                     drop(input);
                     let Err(err) = self.do_exec(theirs, envp.as_ref());
@@ -78,7 +87,7 @@ pub fn spawn(
             }
         };
 
-        let mut p = Process { pid, status: None };
+        let mut p = unsafe { Process::new(pid, pidfd) };
         drop(output);
         let mut bytes = [0; 8];
 
@@ -111,6 +120,119 @@ pub fn spawn(
         }
     }
 
+    // Attempts to fork the process. If successful, returns Ok((0, -1))
+    // in the child, and Ok((child_pid, -1)) in the parent.
+    #[cfg(not(target_os = "linux"))]
+    unsafe fn do_fork(&mut self) -> io::Result<(pid_t, c_int)> {
+        cvt(libc::fork()).map(|res| (res, -1))
+    }
+
+    // Attempts to fork the process. If successful, returns Ok((0, -1))
+    // in the child, and Ok((child_pid, child_pidfd)) in the parent, where
+    // `child_pidfd` is -1 if no pidfd was requested or none could be created.
+    #[cfg(target_os = "linux")]
+    unsafe fn do_fork(&mut self) -> io::Result<(pid_t, c_int)> {
+        use crate::mem;
+        use crate::sync::atomic::{AtomicBool, Ordering};
+        use crate::sys::cvt_r;
+
+        static HAS_CLONE3: AtomicBool = AtomicBool::new(true);
+        const CLONE_PIDFD: u64 = 0x00001000;
+
+        // The first version of `struct clone_args`, as accepted by Linux 5.3.
+        #[repr(C)]
+        #[allow(non_camel_case_types)]
+        struct clone_args {
+            flags: u64,
+            pidfd: u64,
+            child_tid: u64,
+            parent_tid: u64,
+            exit_signal: u64,
+            stack: u64,
+            stack_size: u64,
+            tls: u64,
+        }
+
+        syscall! {
+            fn pidfd_open(pid: pid_t, flags: libc::c_uint) -> c_int
+        }
+
+        if !self.get_create_pidfd() {
+            return cvt(libc::fork()).map(|res| (res, -1));
+        }
+
+        if HAS_CLONE3.load(Ordering::Relaxed) {
+            let mut pidfd: c_int = -1;
+            let mut args = clone_args {
+                flags: CLONE_PIDFD,
+                pidfd: &mut pidfd as *mut c_int as u64,
+                child_tid: 0,
+                parent_tid: 0,
+                exit_signal: libc::SIGCHLD as u64,
+                stack: 0,
+                stack_size: 0,
+                tls: 0,
+            };
+
+            // Without a new stack, `clone3` behaves like `fork`, except that
+            // the `pthread_atfork` handlers aren't run. The child only execs
+            // (or runs the `pre_exec` closures, which already have to be
+            // careful about what they touch after a fork), so this is fine.
+            let res = cvt(libc::syscall(
+                libc::SYS_clone3,
+                &mut args as *mut clone_args,
+                mem::size_of::<clone_args>(),
+            ));
+            match res {
+                Ok(n) => return Ok((n as pid_t, pidfd)),
+                Err(e) => match e.raw_os_error() {
+                    // Multiple threads can race to execute this store, but
+                    // that only means they'll all have tried and failed the
+                    // same syscall once, without any other side effects.
+                    Some(libc::ENOSYS) => HAS_CLONE3.store(false, Ordering::Relaxed),
+                    // Seccomp filters and some container runtimes reject
+                    // `clone3` with EPERM while still allowing `fork`.
+                    Some(libc::EPERM) => {}
+                    _ => return Err(e),
+                },
+            }
+        }
+
+        let pid = cvt(libc::fork())?;
+        if pid == 0 {
+            return Ok((0, -1));
+        }
+
+        // If `pidfd_open` fails (e.g. before Linux 5.3), the child simply
+        // won't have a pidfd.
+        let pidfd = pidfd_open(pid, 0);
+        if pidfd < 0 {
+            return Ok((pid, -1));
+        }
+
+        // Another thread may already have reaped the child, with `waitpid(-1)`
+        // or by ignoring SIGCHLD, and the pid may have been reused before the
+        // call to `pidfd_open`. Only keep the pidfd if it still refers to a
+        // child of ours, without reaping it. From then on it keeps referring
+        // to the same process, even once that has been reaped. If the check
+        // can't be made (`waitid` accepts `P_PIDFD` since Linux 5.4), give up
+        // on the pidfd as well.
+        let mut siginfo: libc::siginfo_t = mem::zeroed();
+        let res = cvt_r(|| {
+            libc::waitid(
+                P_PIDFD,
+                pidfd as libc::id_t,
+                &mut siginfo,
+                libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+            )
+        });
+        if res.is_err() {
+            libc::close(pidfd);
+            return Ok((pid, -1));
+        }
+        Ok((pid, pidfd))
+    }
+
     pub fn exec(&mut self, default: Stdio) -> io::Error {
         let envp = self.capture_env();
 
@@ -294,6 +416,7 @@ fn posix_spawn(
             || (self.env_saw_path() && !self.program_is_path())
             || !self.get_closures().is_empty()
             || self.get_groups().is_some()
+            || self.get_create_pidfd()
         {
             return Ok(None);
         }
@@ -338,7 +461,7 @@ fn posix_spawn_file_actions_addchdir_np(
             None => None,
         };
 
-        let mut p = Process { pid: 0, status: None };
+        let mut p = unsafe { Process::new(0, -1) };
 
         struct PosixSpawnFileActions<'a>(&'a mut MaybeUninit<libc::posix_spawn_file_actions_t>);
 
@@ -427,13 +550,44 @@ fn drop(&mut self) {
 pub struct Process {
     pid: pid_t,
     status: Option<ExitStatus>,
+    // The pidfd of the child, if one was requested and could be created. It
+    // is used instead of the pid to kill and wait on the child, so that those
+    // can't accidentally act on another process that reused the pid.
+    #[cfg(target_os = "linux")]
+    pidfd: Option<PidFd>,
 }
 
 impl Process {
+    #[cfg(not(target_os = "linux"))]
+    unsafe fn new(pid: pid_t, _pidfd: c_int) -> Self {
+        Process { pid, status: None }
+    }
+
+    // Safety: `pidfd` must either be -1 or a valid file descriptor that isn't
+    // owned by anything else.
+    #[cfg(target_os = "linux")]
+    unsafe fn new(pid: pid_t, pidfd: c_int) -> Self {
+        use crate::sys::fd::FileDesc;
+        use crate::sys_common::FromInner;
+
+        let pidfd = if pidfd >= 0 { Some(PidFd::from_inner(FileDesc::new(pidfd))) } else { None };
+        Process { pid, status: None, pidfd }
+    }
+
     pub fn id(&self) -> u32 {
         self.pid as u32
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn pidfd(&self) -> Option<&PidFd> {
+        self.pidfd.as_ref()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn take_pidfd(&mut self) -> Option<PidFd> {
+        self.pidfd.take()
+    }
+
     pub fn kill(&mut self) -> io::Result<()> {
         // If we've already waited on this process then the pid can be recycled
         // and used for another process, and we probably shouldn't be killing
@@ -443,6 +597,8 @@ pub fn kill(&mut self) -> io::Result<()> {
                 ErrorKind::InvalidInput,
                 "invalid argument: can't kill an exited process",
             ))
+        } else if let Some(res) = self.pidfd_kill() {
+            res
         } else {
             cvt(unsafe { libc::kill(self.pid, libc::SIGKILL) }).map(drop)
         }
@@ -453,6 +609,11 @@ pub fn wait(&mut self) -> io::Result<ExitStatus> {
         if let Some(status) = self.status {
             return Ok(status);
         }
+        if let Some(res) = self.pidfd_wait(0) {
+            let status = res?.expect("waitid() without WNOHANG returned no status");
+            self.status = Some(status);
+            return Ok(status);
+        }
         let mut status = 0 as c_int;
         cvt_r(|| unsafe { libc::waitpid(self.pid, &mut status, 0) })?;
         self.status = Some(ExitStatus::new(status));
@@ -463,6 +624,11 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         if let Some(status) = self.status {
             return Ok(Some(status));
         }
+        if let Some(res) = self.pidfd_wait(libc::WNOHANG) {
+            let status = res?;
+            self.status = status;
+            return Ok(status);
+        }
         let mut status = 0 as c_int;
         let pid = cvt(unsafe { libc::waitpid(self.pid, &mut status, libc::WNOHANG) })?;
         if pid == 0 {
@@ -472,6 +638,60 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    // Sends SIGKILL through the pidfd. Returns `None` if there is no pidfd, in
+    // which case the caller falls back to `kill`.
+    #[cfg(not(target_os = "linux"))]
+    fn pidfd_kill(&self) -> Option<io::Result<()>> {
+        None
+    }
+
+    #[cfg(target_os = "linux")]
+    fn pidfd_kill(&self) -> Option<io::Result<()>> {
+        syscall! {
+            fn pidfd_send_signal(
+                pidfd: c_int,
+                sig: c_int,
+                info: *mut libc::siginfo_t,
+                flags: libc::c_uint
+            ) -> c_int
+        }
+
+        // `pidfd_send_signal` (Linux 5.1) is always available when we could
+        // create a pidfd in the first place (Linux 5.3).
+        let pidfd = self.pidfd.as_ref()?.as_raw_fd();
+        Some(cvt(unsafe { pidfd_send_signal(pidfd, libc::SIGKILL, ptr::null_mut(), 0) }).map(drop))
+    }
+
+    // Waits for the child through the pidfd, with `options` being either 0 or
+    // `WNOHANG`. Returns `None` if there is no pidfd or it can't be waited on,
+    // in which case the caller falls back to `waitpid`.
+    #[cfg(not(target_os = "linux"))]
+    fn pidfd_wait(&self, _options: c_int) -> Option<io::Result<Option<ExitStatus>>> {
+        None
+    }
+
+    #[cfg(target_os = "linux")]
+    fn pidfd_wait(&self, options: c_int) -> Option<io::Result<Option<ExitStatus>>> {
+        use crate::mem;
+        use crate::sys::cvt_r;
+
+        let pidfd = self.pidfd.as_ref()?.as_raw_fd();
+        // `si_pid` has to be zeroed to tell whether a `WNOHANG` wait found a
+        // child that changed state.
+        let mut siginfo: libc::siginfo_t = unsafe { mem::zeroed() };
+        let res = cvt_r(|| unsafe {
+            libc::waitid(P_PIDFD, pidfd as libc::id_t, &mut siginfo, libc::WEXITED | options)
+        });
+        match res {
+            // `waitid` only accepts `P_PIDFD` since Linux 5.4, while pidfds
+            // can be created since Linux 5.3.
+            Err(ref e) if e.raw_os_error() == Some(libc::EINVAL) => None,
+            Err(e) => Some(Err(e)),
+            Ok(_) if unsafe { siginfo.si_pid() } == 0 => Some(Ok(None)),
+            Ok(_) => Some(Ok(Some(ExitStatus::from_waitid_siginfo(&siginfo)))),
+        }
+    }
 }
 
 /// Unix exit statuses
@@ -483,6 +703,21 @@ pub fn new(status: c_int) -> ExitStatus {
         ExitStatus(status)
     }
 
+    // Converts the `siginfo_t` filled in by `waitid` to the status `waitpid`
+    // would have returned, so that the rest of `ExitStatus` works unchanged.
+    #[cfg(target_os = "linux")]
+    fn from_waitid_siginfo(siginfo: &libc::siginfo_t) -> ExitStatus {
+        let status = unsafe { siginfo.si_status() };
+        match siginfo.si_code {
+            libc::CLD_EXITED => ExitStatus((status & 0xff) << 8),
+            libc::CLD_KILLED => ExitStatus(status),
+            libc::CLD_DUMPED => ExitStatus(status | 0x80),
+            libc::CLD_CONTINUED => ExitStatus(0xffff),
+            libc::CLD_STOPPED | libc::CLD_TRAPPED => ExitStatus(((status & 0xff) << 8) | 0x7f),
+            _ => unreachable!("waitid() should only return the above codes"),
+        }
+    }
+
     fn exited(&self) -> bool {
         libc::WIFEXITED(self.0)
     }
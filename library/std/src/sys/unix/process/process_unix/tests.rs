@@ -28,3 +28,31 @@ fn exitstatus_display_tests() {
         t(0x000ff, "unrecognised wait status: 255 0xff");
     }
 }
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_command_pidfd() {
+    use crate::os::linux::process::{ChildExt, CommandExt};
+    use crate::os::unix::io::AsRawFd;
+    use crate::os::unix::process::ExitStatusExt;
+    use crate::process::Command;
+
+    let mut child = Command::new("false").create_pidfd(true).spawn().unwrap();
+
+    // pidfds are only available since Linux 5.3, so only poll if we got one.
+    if let Ok(pidfd) = child.pidfd() {
+        let mut pollfd = libc::pollfd { fd: pidfd.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        assert_eq!(unsafe { libc::poll(&mut pollfd, 1, -1) }, 1);
+        assert_eq!(pollfd.revents & libc::POLLIN, libc::POLLIN);
+    }
+    assert_eq!(child.wait().unwrap().code(), Some(1));
+
+    let mut child = Command::new("sleep").arg("1000").create_pidfd(true).spawn().unwrap();
+    child.kill().unwrap();
+    assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGKILL));
+
+    let mut child = Command::new("true").spawn().unwrap();
+    assert!(child.pidfd().is_err());
+    assert!(child.take_pidfd().is_err());
+    child.wait().unwrap();
+}
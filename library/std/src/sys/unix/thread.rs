@@ -2,8 +2,10 @@
 use crate::ffi::CStr;
 use crate::io;
 use crate::mem;
+use crate::num::NonZeroUsize;
 use crate::ptr;
 use crate::sys::{os, stack_overflow};
+use crate::sys_common::thread::unknown_parallelism;
 use crate::time::Duration;
 
 #[cfg(not(any(target_os = "l4re", target_os = "vxworks")))]
@@ -198,6 +200,322 @@ fn drop(&mut self) {
     }
 }
 
+pub fn available_parallelism() -> io::Result<NonZeroUsize> {
+    cfg_if::cfg_if! {
+        if #[cfg(any(target_os = "android", target_os = "linux"))] {
+            let quota = cgroups::quota();
+            let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+            if unsafe { libc::sched_getaffinity(0, mem::size_of_val(&set), &mut set) } == 0 {
+                let count = (0..mem::size_of_val(&set) * 8)
+                    .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+                    .count();
+                // The affinity mask can't be empty, and the quota is at least 1.
+                if let Some(count) = NonZeroUsize::new(cmp::min(count, quota)) {
+                    return Ok(count);
+                }
+            }
+            // Fall back to the number of online CPUs, e.g. if there are more
+            // CPUs than fit in a `cpu_set_t`.
+            match unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) } {
+                -1 => Err(io::Error::last_os_error()),
+                0 => Err(unknown_parallelism()),
+                cpus => {
+                    let cpus = cmp::min(cpus as usize, quota);
+                    Ok(unsafe { NonZeroUsize::new_unchecked(cpus) })
+                }
+            }
+        } else if #[cfg(any(
+            target_os = "emscripten",
+            target_os = "fuchsia",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "solaris",
+            target_os = "illumos",
+        ))] {
+            match unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) } {
+                -1 => Err(io::Error::last_os_error()),
+                0 => Err(unknown_parallelism()),
+                cpus => Ok(unsafe { NonZeroUsize::new_unchecked(cpus as usize) }),
+            }
+        } else if #[cfg(any(
+            target_os = "freebsd",
+            target_os = "dragonfly",
+            target_os = "netbsd",
+        ))] {
+            let mut cpus: libc::c_uint = 0;
+            let mut cpus_size = crate::mem::size_of_val(&cpus);
+
+            unsafe {
+                cpus = libc::sysconf(libc::_SC_NPROCESSORS_ONLN) as libc::c_uint;
+            }
+
+            // Fallback approach in case of errors or no hardware threads.
+            if cpus < 1 {
+                let mut mib = [libc::CTL_HW, libc::HW_NCPU, 0, 0];
+                let res = unsafe {
+                    libc::sysctl(
+                        mib.as_mut_ptr(),
+                        2,
+                        &mut cpus as *mut _ as *mut _,
+                        &mut cpus_size as *mut _ as *mut _,
+                        ptr::null_mut(),
+                        0,
+                    )
+                };
+
+                // Handle errors if any.
+                if res == -1 {
+                    return Err(io::Error::last_os_error());
+                } else if cpus == 0 {
+                    return Err(unknown_parallelism());
+                }
+            }
+            Ok(unsafe { NonZeroUsize::new_unchecked(cpus as usize) })
+        } else if #[cfg(target_os = "openbsd")] {
+            let mut cpus: libc::c_uint = 0;
+            let mut cpus_size = crate::mem::size_of_val(&cpus);
+            let mut mib = [libc::CTL_HW, libc::HW_NCPU, 0, 0];
+
+            let res = unsafe {
+                libc::sysctl(
+                    mib.as_mut_ptr(),
+                    2,
+                    &mut cpus as *mut _ as *mut _,
+                    &mut cpus_size as *mut _ as *mut _,
+                    ptr::null_mut(),
+                    0,
+                )
+            };
+
+            // Handle errors if any.
+            if res == -1 {
+                return Err(io::Error::last_os_error());
+            } else if cpus == 0 {
+                return Err(unknown_parallelism());
+            }
+
+            Ok(unsafe { NonZeroUsize::new_unchecked(cpus as usize) })
+        } else {
+            // FIXME: implement on vxWorks, Redox, Haiku, l4re
+            Err(unknown_parallelism())
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod cgroups {
+    //! Reads the CPU quota of the cgroup the process runs in, as set by
+    //! container runtimes with e.g. `docker run --cpus`.
+    //!
+    //! Not covered:
+    //! * cgroup v2 hierarchies mounted anywhere but `/sys/fs/cgroup`
+    //! * paths containing spaces or control characters, which are escaped in
+    //!   procfs output and which we don't unescape
+
+    #[cfg(test)]
+    mod tests;
+
+    use crate::cmp;
+    use crate::ffi::OsStr;
+    use crate::fs::{self, File};
+    use crate::io::Read;
+    use crate::os::unix::ffi::OsStrExt;
+    use crate::path::{Path, PathBuf};
+    use crate::str;
+
+    /// The standard mount point of cgroup hierarchies, from file-hierarchy(7).
+    const CGROUP_MOUNT: &str = "/sys/fs/cgroup";
+
+    #[derive(Debug, PartialEq)]
+    enum Cgroup {
+        V1,
+        V2,
+    }
+
+    /// Returns the CPU quota of the cgroup in core-equivalents, rounded down
+    /// but at least 1, or `usize::MAX` if there is no quota or it can't be
+    /// determined.
+    pub(super) fn quota() -> usize {
+        let mut buf = Vec::with_capacity(128);
+        if File::open("/proc/self/cgroup").and_then(|mut f| f.read_to_end(&mut buf)).is_err() {
+            return usize::MAX;
+        }
+        match parse_cgroup_path(&buf) {
+            Some((path, Cgroup::V1)) => quota_v1(Path::new(OsStr::from_bytes(path))),
+            Some((path, Cgroup::V2)) => quota_v2(Path::new(OsStr::from_bytes(path))),
+            None => usize::MAX,
+        }
+    }
+
+    /// Finds the place of the process in the cgroup hierarchy that has the
+    /// `cpu` controller, relative to its root, in the contents of
+    /// `/proc/self/cgroup`.
+    fn parse_cgroup_path(cgroups: &[u8]) -> Option<(&[u8], Cgroup)> {
+        // Each line is `hierarchy-ID:controller-list:cgroup-path`.
+        let mut found: Option<(&[u8], Cgroup)> = None;
+        for line in cgroups.split(|&c| c == b'\n') {
+            let mut fields = line.splitn(3, |&c| c == b':');
+            // The controller list is empty for cgroup v2.
+            let version = match fields.nth(1) {
+                Some(b"") => Cgroup::V2,
+                Some(controllers)
+                    if str::from_utf8(controllers)
+                        .map_or(false, |c| c.split(',').any(|c| c == "cpu")) =>
+                {
+                    Cgroup::V1
+                }
+                _ => continue,
+            };
+            // A v1 hierarchy explicitly lists the `cpu` controller, so it
+            // takes precedence over the v2 one.
+            if found.is_some() && version == Cgroup::V2 {
+                continue;
+            }
+            if let Some(path) = fields.next() {
+                found = Some((path, version));
+            }
+        }
+
+        let (path, version) = found?;
+        // Skip the leading slash, so that the path can be pushed onto a mount point.
+        Some((path.strip_prefix(b"/").unwrap_or(path), version))
+    }
+
+    /// Parses the contents of a `cpu.max` file, which holds `$MAX $PERIOD`,
+    /// where `$MAX` is `max` if there is no limit.
+    fn parse_cpu_max(cpu_max: &str) -> Option<usize> {
+        let mut fields = cpu_max.split_whitespace();
+        let limit = fields.next()?.parse().ok()?;
+        let period = fields.next()?.parse().ok()?;
+        cpus_from_quota(limit, period)
+    }
+
+    /// Parses the contents of the `cpu.cfs_quota_us` and `cpu.cfs_period_us`
+    /// files, where a quota of `-1` means no limit.
+    fn parse_cfs_quota(quota: &str, period: &str) -> Option<usize> {
+        cpus_from_quota(quota.trim().parse().ok()?, period.trim().parse().ok()?)
+    }
+
+    /// Converts a quota of CPU time per period into core-equivalents.
+    ///
+    /// A quota below one CPU still allows running one thread at a time, so
+    /// it counts as one.
+    fn cpus_from_quota(limit: usize, period: usize) -> Option<usize> {
+        if period == 0 {
+            return None;
+        }
+        Some(cmp::max(limit / period, 1))
+    }
+
+    /// Reads the `cpu.max` files of the cgroup and its ancestors.
+    fn quota_v2(group_path: &Path) -> usize {
+        let mut path = PathBuf::from(CGROUP_MOUNT);
+        path.push(group_path);
+
+        // Skip if the standard mount point isn't a cgroup v2 hierarchy.
+        if fs::metadata(path.join("cgroup.controllers")).is_err() {
+            return usize::MAX;
+        }
+
+        let mut quota = usize::MAX;
+        let mut buf = String::with_capacity(20);
+        while path.starts_with(CGROUP_MOUNT) {
+            buf.clear();
+            if File::open(path.join("cpu.max")).and_then(|mut f| f.read_to_string(&mut buf)).is_ok()
+            {
+                if let Some(cpus) = parse_cpu_max(&buf) {
+                    quota = quota.min(cpus);
+                }
+            }
+            path.pop();
+        }
+        quota
+    }
+
+    /// Reads the `cpu.cfs_quota_us` and `cpu.cfs_period_us` files of the
+    /// cgroup and its ancestors.
+    fn quota_v1(group_path: &Path) -> usize {
+        // Try the locations mentioned in cgroups(7) first, and only scan the
+        // mount table if the cgroup isn't there.
+        let mount = ["/sys/fs/cgroup/cpu", "/sys/fs/cgroup/cpu,cpuacct"]
+            .iter()
+            .map(|&mount| (PathBuf::from(mount), group_path))
+            .find(|(mount, group_path)| mount.join(group_path).is_dir())
+            .or_else(|| find_mountpoint_v1(group_path));
+        let (mount, group_path) = match mount {
+            Some(mount) => mount,
+            None => return usize::MAX,
+        };
+
+        let read_file = |path: &Path| -> Option<String> {
+            let mut buf = String::with_capacity(20);
+            File::open(path).ok()?.read_to_string(&mut buf).ok()?;
+            Some(buf)
+        };
+
+        let mut quota = usize::MAX;
+        let mut path = mount.join(group_path);
+        while path.starts_with(&mount) {
+            let limit = read_file(&path.join("cpu.cfs_quota_us"));
+            let period = read_file(&path.join("cpu.cfs_period_us"));
+            if let (Some(limit), Some(period)) = (limit, period) {
+                if let Some(cpus) = parse_cfs_quota(&limit, &period) {
+                    quota = quota.min(cpus);
+                }
+            }
+            path.pop();
+        }
+        quota
+    }
+
+    /// Scans the mount table for a cgroup v1 hierarchy with the `cpu`
+    /// controller that contains `group_path`.
+    fn find_mountpoint_v1(group_path: &Path) -> Option<(PathBuf, &Path)> {
+        let mut mountinfo = String::with_capacity(4096);
+        File::open("/proc/self/mountinfo").ok()?.read_to_string(&mut mountinfo).ok()?;
+        let (mount_point, group_path) = parse_mountinfo_v1(&mountinfo, group_path)?;
+        Some((PathBuf::from(mount_point), group_path))
+    }
+
+    /// Finds the mount point of the cgroup v1 hierarchy with the `cpu`
+    /// controller in the contents of `/proc/self/mountinfo`.
+    ///
+    /// If the hierarchy is a bind mount of a subdirectory of the cgroup root,
+    /// as is common in containers, the returned path is relative to it.
+    fn parse_mountinfo_v1<'a, 'b>(
+        mountinfo: &'a str,
+        group_path: &'b Path,
+    ) -> Option<(&'a str, &'b Path)> {
+        for line in mountinfo.lines() {
+            // See proc(5) for the format of the lines: the root of the mount
+            // and the mount point are the 4th and 5th fields, while the
+            // filesystem type and the super block options are the 3rd and
+            // last but one from the end. Lines that don't match are skipped.
+            let mut fields = line.trim().split(' ');
+            let (root, mount_point, super_options, filesystem_type) =
+                match (fields.nth(3), fields.next(), fields.next_back(), fields.nth_back(1)) {
+                    (Some(root), Some(mount_point), Some(super_options), Some(filesystem_type)) => {
+                        (root, mount_point, super_options, filesystem_type)
+                    }
+                    _ => continue,
+                };
+
+            if filesystem_type != "cgroup" || !super_options.split(',').any(|opt| opt == "cpu") {
+                continue;
+            }
+
+            let root = match Path::new(root).strip_prefix("/") {
+                Ok(root) => root,
+                Err(_) => continue,
+            };
+            if let Ok(group_path) = group_path.strip_prefix(root) {
+                return Some((mount_point, group_path));
+            }
+        }
+        None
+    }
+}
+
 #[cfg(all(
     not(target_os = "linux"),
     not(target_os = "freebsd"),
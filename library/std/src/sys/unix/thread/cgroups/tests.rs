@@ -0,0 +1,105 @@
+use super::*;
+
+#[test]
+fn cgroup_path_v1() {
+    let cgroup = b"12:memory:/docker/0123abcd\n\
+                   11:cpu,cpuacct:/docker/0123abcd\n\
+                   1:name=systemd:/docker/0123abcd\n\
+                   0::/system.slice/docker.service\n";
+    assert_eq!(parse_cgroup_path(cgroup), Some((&b"docker/0123abcd"[..], Cgroup::V1)));
+
+    // The v1 `cpu` hierarchy wins regardless of the order of the lines.
+    let cgroup = b"0::/user.slice\n4:cpu:/user.slice/cpu-limited\n";
+    assert_eq!(parse_cgroup_path(cgroup), Some((&b"user.slice/cpu-limited"[..], Cgroup::V1)));
+}
+
+#[test]
+fn cgroup_path_v2() {
+    let cgroup = b"0::/user.slice/user-1000.slice/session-1.scope\n";
+    assert_eq!(
+        parse_cgroup_path(cgroup),
+        Some((&b"user.slice/user-1000.slice/session-1.scope"[..], Cgroup::V2))
+    );
+
+    let cgroup = b"0::/\n";
+    assert_eq!(parse_cgroup_path(cgroup), Some((&b""[..], Cgroup::V2)));
+}
+
+#[test]
+fn cgroup_path_without_cpu_controller() {
+    assert_eq!(parse_cgroup_path(b"12:memory:/docker/0123abcd\n3:cpuset:/docker/0123abcd\n"), None);
+    assert_eq!(parse_cgroup_path(b""), None);
+}
+
+#[test]
+fn cpu_max() {
+    assert_eq!(parse_cpu_max("max 100000\n"), None);
+    assert_eq!(parse_cpu_max("200000 100000\n"), Some(2));
+    assert_eq!(parse_cpu_max("250000 100000\n"), Some(2));
+    assert_eq!(parse_cpu_max("100000 0\n"), None);
+    assert_eq!(parse_cpu_max(""), None);
+}
+
+#[test]
+fn cfs_quota() {
+    assert_eq!(parse_cfs_quota("-1\n", "100000\n"), None);
+    assert_eq!(parse_cfs_quota("400000\n", "100000\n"), Some(4));
+    assert_eq!(parse_cfs_quota("150000\n", "100000\n"), Some(1));
+    assert_eq!(parse_cfs_quota("100000\n", "0\n"), None);
+}
+
+#[test]
+fn quota_below_one_cpu() {
+    assert_eq!(parse_cpu_max("50000 100000\n"), Some(1));
+    assert_eq!(parse_cfs_quota("1000\n", "100000\n"), Some(1));
+}
+
+#[test]
+fn mountinfo_v1() {
+    let mountinfo = "\
+        25 1 0:23 / /sys/fs/cgroup ro shared:9 - tmpfs tmpfs ro,mode=755\n\
+        31 25 0:27 / /sys/fs/cgroup/memory rw shared:13 - cgroup cgroup rw,memory\n\
+        33 25 0:29 / /cgroups/cpu rw shared:15 - cgroup cgroup rw,cpu,cpuacct\n";
+    assert_eq!(
+        parse_mountinfo_v1(mountinfo, Path::new("docker/0123abcd")),
+        Some(("/cgroups/cpu", Path::new("docker/0123abcd")))
+    );
+}
+
+#[test]
+fn mountinfo_v1_bind_mounted_root() {
+    // Inside a container, only the container's own cgroup is mounted.
+    let mountinfo = "\
+        35 30 0:27 /docker/0123abcd /sys/fs/cgroup/memory ro - cgroup cgroup rw,memory\n\
+        37 30 0:29 /docker/0123abcd /sys/fs/cgroup/cpu,cpuacct ro - cgroup cgroup rw,cpu,cpuacct\n";
+    assert_eq!(
+        parse_mountinfo_v1(mountinfo, Path::new("docker/0123abcd")),
+        Some(("/sys/fs/cgroup/cpu,cpuacct", Path::new("")))
+    );
+    assert_eq!(
+        parse_mountinfo_v1(mountinfo, Path::new("docker/0123abcd/nested")),
+        Some(("/sys/fs/cgroup/cpu,cpuacct", Path::new("nested")))
+    );
+    // A cgroup outside of the bind-mounted subtree isn't reachable.
+    assert_eq!(parse_mountinfo_v1(mountinfo, Path::new("docker/4567ef01")), None);
+}
+
+#[test]
+fn mountinfo_with_malformed_lines() {
+    let mountinfo = "\
+        25 1 0:23\n\
+        \n\
+        33 25 0:29 / /cgroups/cpu rw shared:15 - cgroup cgroup rw,cpu,cpuacct\n";
+    assert_eq!(
+        parse_mountinfo_v1(mountinfo, Path::new("docker/0123abcd")),
+        Some(("/cgroups/cpu", Path::new("docker/0123abcd")))
+    );
+}
+
+#[test]
+fn mountinfo_without_cpu_hierarchy() {
+    let mountinfo = "\
+        26 25 0:24 / /sys/fs/cgroup/unified rw shared:10 - cgroup2 cgroup2 rw\n\
+        31 25 0:27 / /sys/fs/cgroup/memory rw shared:13 - cgroup cgroup rw,memory\n";
+    assert_eq!(parse_mountinfo_v1(mountinfo, Path::new("user.slice")), None);
+}
@@ -3,7 +3,9 @@
 use crate::ffi::CStr;
 use crate::io;
 use crate::mem;
+use crate::num::NonZeroUsize;
 use crate::sys::{unsupported, Void};
+use crate::sys_common::thread::unknown_parallelism;
 use crate::time::Duration;
 
 pub struct Thread(Void);
@@ -63,6 +65,10 @@ pub fn join(self) {
     }
 }
 
+pub fn available_parallelism() -> io::Result<NonZeroUsize> {
+    Err(unknown_parallelism())
+}
+
 pub mod guard {
     pub type Guard = !;
     pub unsafe fn current() -> Option<Guard> {
@@ -1,6 +1,8 @@
 use crate::ffi::CStr;
 use crate::io;
+use crate::num::NonZeroUsize;
 use crate::sys::{unsupported, Void};
+use crate::sys_common::thread::unknown_parallelism;
 use crate::time::Duration;
 
 pub struct Thread(Void);
@@ -51,6 +53,10 @@ pub fn join(self) {
     }
 }
 
+pub fn available_parallelism() -> io::Result<NonZeroUsize> {
+    Err(unknown_parallelism())
+}
+
 pub mod guard {
     pub type Guard = !;
     pub unsafe fn current() -> Option<Guard> {
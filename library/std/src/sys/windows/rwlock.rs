@@ -5,6 +5,9 @@ pub struct RWLock {
     inner: UnsafeCell<c::SRWLOCK>,
 }
 
+// Windows SRW Locks are movable (while not borrowed).
+pub type MovableRWLock = RWLock;
+
 unsafe impl Send for RWLock {}
 unsafe impl Sync for RWLock {}
 
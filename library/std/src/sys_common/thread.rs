@@ -1,4 +1,5 @@
 use crate::env;
+use crate::io;
 use crate::sync::atomic::{self, Ordering};
 use crate::sys::thread as imp;
 
@@ -16,3 +17,12 @@ pub fn min_stack() -> usize {
     MIN.store(amt + 1, Ordering::SeqCst);
     amt
 }
+
+/// The error returned by `available_parallelism` on platforms where the number
+/// of hardware threads can't be determined.
+pub fn unknown_parallelism() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        "The number of hardware threads is not known for the target platform",
+    )
+}
@@ -0,0 +1,54 @@
+use crate::io;
+use crate::num::NonZeroUsize;
+use crate::sys::thread as imp;
+
+/// Returns an estimate of the default amount of parallelism a program should use.
+///
+/// Parallelism is a resource. A given machine provides a certain capacity for
+/// parallelism, i.e., a bound on the number of computations it can perform
+/// simultaneously. This number often corresponds to the amount of CPUs a
+/// computer has, but it may diverge in various cases.
+///
+/// Host environments such as VMs or container orchestrators may want to
+/// restrict the amount of parallelism made available to programs in them. This
+/// is often done to limit the potential impact of (unintentionally)
+/// resource-intensive programs on other programs running on the same machine.
+///
+/// This value should be considered only a hint, and is computed every time
+/// this function is called: it can change while the program is running.
+///
+/// # Platform-specific behavior
+///
+/// On Linux and Android, the number of CPUs in the affinity mask of the
+/// process is used, and it is further limited by the CPU quota of the cgroup
+/// (v1 or v2) the process runs in, rounded down to a whole number of CPUs but
+/// never below one. Cgroup v2 hierarchies are only looked up at their standard
+/// mount point, `/sys/fs/cgroup`.
+///
+/// On other platforms, this is the number of online hardware threads. It may
+/// undercount on Windows systems with more than 64 hardware threads, and it may
+/// overcount on Windows when limited by a process wide affinity mask or job
+/// object limitations.
+///
+/// # Errors
+///
+/// This function will return an error in the following situations, but is not
+/// limited to just these cases:
+///
+/// - If the amount of parallelism is not known for the target platform.
+/// - If the program lacks permission to query the amount of parallelism made
+///   available to it.
+///
+/// # Examples
+///
+/// ```
+/// # #![allow(dead_code)]
+/// #![feature(available_parallelism)]
+/// use std::thread;
+///
+/// let count = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+/// ```
+#[unstable(feature = "available_parallelism", issue = "74479")]
+pub fn available_parallelism() -> io::Result<NonZeroUsize> {
+    imp::available_parallelism()
+}
@@ -258,5 +258,66 @@ fn test_thread_id_not_equal() {
     assert!(thread::current().id() != spawned_id);
 }
 
+#[test]
+fn test_scoped_threads_borrow_locals() {
+    let mut a = vec![1, 2, 3];
+    let mut sum = 0;
+    thread::scope(|s| {
+        s.spawn(|| assert_eq!(a.len(), 3));
+        s.spawn(|| sum = a.iter().sum::<i32>());
+    });
+    a.push(4);
+    assert_eq!(sum, 6);
+}
+
+#[test]
+fn test_scoped_threads_join() {
+    let x = 21;
+    let result = thread::scope(|s| s.spawn(|| x * 2).join().unwrap());
+    assert_eq!(result, 42);
+}
+
+#[test]
+fn test_scoped_threads_joined_panic() {
+    thread::scope(|s| {
+        let handle = s.spawn(|| panic!());
+        assert!(handle.join().is_err());
+    });
+}
+
+#[test]
+fn test_scoped_threads_unjoined_panic() {
+    let result = crate::panic::catch_unwind(|| {
+        thread::scope(|s| {
+            s.spawn(|| panic!());
+        });
+    });
+    assert!(result.is_err());
+}
+
 // NOTE: the corresponding test for stderr is in ui/thread-stderr, due
 // to the test harness apparently interfering with stderr configuration.
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", windows))]
+fn test_available_parallelism() {
+    // None of the fallbacks on these platforms can fail.
+    thread::available_parallelism().unwrap();
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn test_available_parallelism_affinity() {
+    // Pin a thread to a single CPU, which must then be all it sees.
+    thread::spawn(|| unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        assert_eq!(libc::sched_getaffinity(0, mem::size_of_val(&set), &mut set), 0);
+        let cpu = (0..mem::size_of_val(&set) * 8).find(|&cpu| libc::CPU_ISSET(cpu, &set)).unwrap();
+        libc::CPU_ZERO(&mut set);
+        libc::CPU_SET(cpu, &mut set);
+        assert_eq!(libc::sched_setaffinity(0, mem::size_of_val(&set), &set), 0);
+        assert_eq!(thread::available_parallelism().unwrap().get(), 1);
+    })
+    .join()
+    .unwrap();
+}
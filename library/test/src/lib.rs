@@ -23,7 +23,7 @@
 #![cfg_attr(unix, feature(libc))]
 #![feature(rustc_private)]
 #![feature(nll)]
-#![feature(available_concurrency)]
+#![feature(available_parallelism)]
 #![feature(internal_output_capture)]
 #![feature(option_unwrap_none)]
 #![feature(panic_unwind)]
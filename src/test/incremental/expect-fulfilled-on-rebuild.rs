@@ -0,0 +1,25 @@
+// Checks that `#[expect]` attributes stay fulfilled when rebuilding without
+// changes, even though the lints fulfilling them are not emitted again because
+// the results of the passes that emitted them are reused.
+
+// revisions: cfail1 cfail2 cfail3
+// build-pass
+
+#![feature(lint_reasons)]
+#![deny(unfulfilled_lint_expectations)]
+
+fn main() {
+    #[expect(unused_variables)]
+    let unused = 2;
+
+    overflows();
+}
+
+#[expect(dead_code)]
+fn dead() {}
+
+// This lint is emitted by const propagation when building the MIR for codegen.
+#[expect(arithmetic_overflow)]
+fn overflows() -> u8 {
+    255u8 + 1
+}
@@ -0,0 +1,15 @@
+// Checks that rebuilding without changes reports the same warnings as a clean
+// build, even for the passes whose results are reused from the previous session.
+
+// revisions: cfail1 cfail2 cfail3
+// build-pass
+
+#![warn(unused)]
+
+fn main() {
+    let mut x = 1u8; //~ WARNING variable does not need to be mutable
+    let _y = x + 1;
+    let unused = 2; //~ WARNING unused variable
+}
+
+fn dead() {} //~ WARNING function is never used
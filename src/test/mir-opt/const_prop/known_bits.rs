@@ -0,0 +1,16 @@
+// compile-flags: -Z mir-opt-level=2
+// Checks that the discriminant of a switch is replaced by the value it has on
+// every path reaching the switch, so that the switch is removed.
+
+// EMIT_MIR known_bits.odd.SimplifyBranches-after-const-prop.diff
+fn odd(c: bool) -> u32 {
+    let x = if c { 5_u8 } else { 7_u8 };
+    match x & 1 {
+        1 => 10,
+        _ => 20,
+    }
+}
+
+fn main() {
+    odd(true);
+}
@@ -0,0 +1,13 @@
+// Checks that `GlobalValueNumbering` replaces a computation whose result is
+// already available in a local by a copy of that local.
+
+// EMIT_MIR gvn.repeated.GlobalValueNumbering.diff
+fn repeated(a: u32, b: u32) -> u32 {
+    let x = a ^ b;
+    let y = a ^ b;
+    x & y
+}
+
+fn main() {
+    repeated(1, 2);
+}
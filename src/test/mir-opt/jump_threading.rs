@@ -0,0 +1,17 @@
+// Checks that `JumpThreading` redirects the jumps to a `SwitchInt` on a local
+// that each predecessor has just set to a constant.
+//
+// compile-flags: -Z mir-opt-level=3
+
+// EMIT_MIR jump_threading.select.JumpThreading.diff
+fn select(x: bool) -> u32 {
+    let n = if x { 1_u8 } else { 2_u8 };
+    match n {
+        1 => 10,
+        _ => 20,
+    }
+}
+
+fn main() {
+    select(true);
+}
@@ -0,0 +1,12 @@
+// Checks that `ScalarReplacementOfAggregates` replaces a tuple that is only
+// used through its fields by one local per field.
+
+// EMIT_MIR sroa.pair.ScalarReplacementOfAggregates.diff
+fn pair(a: u32, b: u32) -> u32 {
+    let t = (a, b);
+    t.0
+}
+
+fn main() {
+    pair(1, 2);
+}
@@ -0,0 +1,13 @@
+#![warn(unused_mut, unused_variables)]
+
+pub fn sum(a: u32, b: u32) -> u32 {
+    let mut total = a + b;
+    let unused = 0;
+    total
+}
+
+pub fn untouched() -> u32 {
+    let mut x = 1;
+    x += 1;
+    x
+}
@@ -0,0 +1,15 @@
+fn longest<'a>(x: &'a str, y: &str) -> &'a str {
+    if x.len() > y.len() { x } else { y }
+}
+
+fn conflict() {
+    let mut v = vec![1];
+    let first = &v[0];
+    v.push(2);
+    println!("{}", first);
+}
+
+fn main() {
+    longest("a", "b");
+    conflict();
+}
@@ -0,0 +1,29 @@
+#[inline]
+fn small(x: u32) -> u32 {
+    x + 1
+}
+
+#[inline(never)]
+fn never(x: u32) -> u32 {
+    x * 2
+}
+
+fn not_exported(x: u32) -> u32 {
+    x * 3
+}
+
+#[inline]
+fn large(v: &mut Vec<u32>) {
+    v.push(1);
+    v.push(2);
+    v.push(3);
+    v.push(4);
+    v.push(5);
+}
+
+fn main() {
+    let mut v = vec![never(small(1))];
+    v.push(not_exported(2));
+    large(&mut v);
+    println!("{:?}", v);
+}
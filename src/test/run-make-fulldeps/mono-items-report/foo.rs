@@ -0,0 +1,9 @@
+#![crate_type = "lib"]
+
+pub fn generic<T: Default>() -> T {
+    T::default()
+}
+
+pub fn not_generic() -> (u8, u32) {
+    (generic::<u8>(), generic::<u32>())
+}
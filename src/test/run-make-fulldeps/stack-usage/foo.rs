@@ -0,0 +1,18 @@
+#![crate_type = "lib"]
+
+fn fill() -> u8 {
+    let buffer = [0u8; 65536];
+    buffer[0]
+}
+
+fn small() -> u8 {
+    1
+}
+
+pub fn entry() -> u8 {
+    fill() + small()
+}
+
+pub fn countdown(n: u32) -> u32 {
+    if n == 0 { 0 } else { countdown(n - 1) }
+}
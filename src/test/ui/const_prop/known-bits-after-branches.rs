@@ -0,0 +1,33 @@
+// build-pass
+// Checks that values known on every path reaching an operation are used to
+// report panics and overflows, even when they are assigned in different blocks.
+// These findings are reported by their own warn-by-default lint.
+
+fn same_value_in_both_branches(c: bool) -> u8 {
+    let array = [1u8, 2, 3];
+    let mut i = 3;
+    if c {
+        i = 3;
+    }
+    array[i] //~ WARN this operation will panic at runtime
+}
+
+fn range_of_both_branches(c: bool) -> u8 {
+    let array = [1u8, 2, 3, 4];
+    let i = if c { 5 } else { 7 };
+    array[i] //~ WARN this operation will panic at runtime
+}
+
+fn overflow_after_branch(c: bool) -> u8 {
+    let mut x = 200u8;
+    if c {
+        x = 200;
+    }
+    x + 100 //~ WARN this arithmetic operation will overflow
+}
+
+fn main() {
+    same_value_in_both_branches(true);
+    range_of_both_branches(true);
+    overflow_after_branch(true);
+}
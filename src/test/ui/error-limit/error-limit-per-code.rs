@@ -0,0 +1,10 @@
+// compile-flags: -Z error-limit-per-code=2
+
+fn f(_: u8) {}
+
+fn main() {
+    f("a"); //~ ERROR mismatched types
+    f("b"); //~ ERROR mismatched types
+    f("c");
+    f("d");
+}
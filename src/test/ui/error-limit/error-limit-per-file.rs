@@ -0,0 +1,11 @@
+// check-pass
+// compile-flags: -Z error-limit-per-file=2
+
+#![warn(unused_variables)]
+
+fn main() {
+    let a = 1; //~ WARN unused variable: `a`
+    let b = 2; //~ WARN unused variable: `b`
+    let c = 3;
+    let d = 4;
+}
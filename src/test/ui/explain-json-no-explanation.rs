@@ -0,0 +1,2 @@
+// compile-flags: --explain E0208 --explain-format=json -Z unstable-options
+// check-pass
@@ -0,0 +1,2 @@
+// compile-flags: --explain E0670 --explain-format=json -Z unstable-options
+// check-pass
@@ -0,0 +1,5 @@
+#[expect(dead_code)]
+//~^ ERROR the `#[expect]` attribute is an experimental feature
+fn unused() {}
+
+fn main() {}
@@ -0,0 +1,46 @@
+// build-fail
+#![crate_type = "lib"]
+#![deny(missing_inline_hint)]
+
+pub fn answer() -> u32 { //~ ERROR `answer` is small enough to be inlined into other crates
+    42
+}
+
+#[inline]
+pub fn already_inline() -> u32 {
+    42
+}
+
+pub fn generic<T: Default>() -> T {
+    T::default()
+}
+
+#[no_mangle]
+pub extern "C" fn exported_symbol() -> u32 {
+    42
+}
+
+pub fn large(v: &mut Vec<u32>) {
+    v.push(1);
+    v.push(2);
+    v.push(3);
+    v.push(4);
+    v.push(5);
+}
+
+fn private() -> u32 {
+    42
+}
+
+pub fn calls_private() -> u32 {
+    private()
+}
+
+#[inline]
+fn private_inline() -> u32 {
+    42
+}
+
+pub fn calls_private_inline() -> u32 { //~ ERROR `calls_private_inline` is small enough
+    private_inline()
+}
@@ -0,0 +1,32 @@
+// Checks that expectations of the lints emitted by const propagation, which only
+// runs when building the MIR for codegen, are fulfilled by a full build, and are
+// not reported as unfulfilled when there is no codegen.
+
+// revisions: check build
+//[check] check-pass
+//[build] build-pass
+// ignore-pass (the revisions differ in whether there is codegen)
+
+#![feature(lint_reasons)]
+
+#[expect(arithmetic_overflow)]
+fn overflows() -> u8 {
+    255u8 + 1
+}
+
+#[expect(unconditional_panic)]
+fn divides_by_zero() -> i32 {
+    1 / 0
+}
+
+#[expect(arithmetic_overflow)]
+//[build]~^ WARN this lint expectation is unfulfilled [unfulfilled_lint_expectations]
+fn does_not_overflow() -> u8 {
+    254u8 + 1
+}
+
+fn main() {
+    overflows();
+    divides_by_zero();
+    does_not_overflow();
+}
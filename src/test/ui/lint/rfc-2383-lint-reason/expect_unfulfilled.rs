@@ -0,0 +1,42 @@
+// check-pass
+
+#![feature(lint_reasons)]
+
+fn contains_unused_fn() {
+    #[expect(dead_code)]
+    fn unused_fn() {}
+}
+
+#[expect(dead_code)]
+//~^ WARN this lint expectation is unfulfilled [unfulfilled_lint_expectations]
+//~| NOTE `#[warn(unfulfilled_lint_expectations)]` on by default
+fn used_fn() {}
+
+#[expect(unused_variables)]
+fn fulfilled_by_inner_item() {
+    let x = 1;
+}
+
+#[allow(unfulfilled_lint_expectations)]
+#[expect(unused_mut)]
+fn unfulfilled_but_allowed() {
+    let mut x = 0;
+    x += 1;
+    println!("{}", x);
+}
+
+fn main() {
+    contains_unused_fn();
+    used_fn();
+    fulfilled_by_inner_item();
+    unfulfilled_but_allowed();
+
+    #[expect(unused_variables, reason = "the value is only computed for its side effects")]
+    let computed = 2;
+
+    #[expect(unused_variables, reason = "the value is printed below")]
+    //~^ WARN this lint expectation is unfulfilled [unfulfilled_lint_expectations]
+    //~| NOTE the value is printed below
+    let printed = 3;
+    println!("{}", printed);
+}
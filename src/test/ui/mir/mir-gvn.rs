@@ -0,0 +1,79 @@
+// Checks that replacing repeated computations by copies of their first result
+// keeps the behavior of the program.
+// run-pass
+// compile-flags: -Zmir-opt-level=3 -Zvalidate-mir
+
+enum Shape {
+    Circle(u32),
+    Square(u32),
+    Empty,
+}
+
+#[inline(never)]
+fn repeated_binops(a: u32, b: u32) -> u32 {
+    let x = a.wrapping_mul(b) + 1;
+    let y = a.wrapping_mul(b) + 2;
+    let z = (a ^ b) << 1;
+    let w = (a ^ b) << 1;
+    x + y + z + w
+}
+
+#[inline(never)]
+fn repeated_len(v: &[u8], i: usize, j: usize) -> u8 {
+    v[i] + v[j] + v.len() as u8
+}
+
+#[inline(never)]
+fn repeated_discriminant(shape: &Shape) -> u32 {
+    let first = match shape {
+        Shape::Circle(r) => *r,
+        Shape::Square(s) => *s * 2,
+        Shape::Empty => 0,
+    };
+    let second = match shape {
+        Shape::Circle(_) => 1,
+        Shape::Square(_) => 2,
+        Shape::Empty => 3,
+    };
+    first + second
+}
+
+#[inline(never)]
+fn compare_with_itself(a: i64, f: f64) -> (bool, bool, bool) {
+    let b = a + 1;
+    // `NaN != NaN`, so comparisons of floats with themselves are not constant.
+    (b == b, b < b, f == f)
+}
+
+#[inline(never)]
+fn in_loop(v: &[u32]) -> u32 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < v.len() {
+        let x = v[i] * 3;
+        let y = v[i] * 3;
+        total += x + y;
+        i += 1;
+    }
+    total
+}
+
+#[inline(never)]
+fn reassigned(mut a: u32, b: u32) -> u32 {
+    let x = a + b;
+    a += 1;
+    let y = a + b;
+    x * y
+}
+
+fn main() {
+    assert_eq!(repeated_binops(3, 5), 16 + 17 + 12 + 12);
+    assert_eq!(repeated_len(&[1, 2, 3], 0, 2), 7);
+    assert_eq!(repeated_discriminant(&Shape::Circle(4)), 5);
+    assert_eq!(repeated_discriminant(&Shape::Square(4)), 10);
+    assert_eq!(repeated_discriminant(&Shape::Empty), 3);
+    assert_eq!(compare_with_itself(1, 1.0), (true, false, true));
+    assert_eq!(compare_with_itself(1, f64::NAN), (true, false, false));
+    assert_eq!(in_loop(&[1, 2, 3]), 36);
+    assert_eq!(reassigned(1, 2), 12);
+}
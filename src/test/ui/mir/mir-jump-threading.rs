@@ -0,0 +1,86 @@
+// Checks that redirecting jumps to switches whose outcome is already known
+// keeps the behavior of the program.
+// run-pass
+// compile-flags: -Zmir-opt-level=3 -Zvalidate-mir
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    Negative,
+    TooLarge,
+}
+
+#[inline]
+fn check_sign(x: i32) -> Result<u32, Error> {
+    if x < 0 { Err(Error::Negative) } else { Ok(x as u32) }
+}
+
+#[inline]
+fn check_size(x: u32) -> Result<u32, Error> {
+    if x > 100 { Err(Error::TooLarge) } else { Ok(x) }
+}
+
+#[inline(never)]
+fn question_marks(x: i32) -> Result<u32, Error> {
+    let x = check_sign(x)?;
+    let x = check_size(x)?;
+    Ok(x * 2)
+}
+
+#[inline(never)]
+fn option_then_match(x: u8) -> u8 {
+    let o = if x % 2 == 0 { Some(x / 2) } else { None };
+    match o {
+        Some(y) => y,
+        None => 255,
+    }
+}
+
+#[inline(never)]
+fn bool_flag(x: u32) -> u32 {
+    let mut flag = false;
+    if x > 10 {
+        flag = true;
+    }
+    if flag { x - 10 } else { x }
+}
+
+#[inline(never)]
+fn in_loop(v: &[i32]) -> Option<i32> {
+    let mut state = Some(0);
+    for &x in v {
+        state = match state {
+            Some(total) if x >= 0 => Some(total + x),
+            _ => None,
+        };
+    }
+    state
+}
+
+#[inline(never)]
+fn indexed_discriminant(c: bool) -> u8 {
+    let mut a = [None, Some(2)];
+    let mut i = 0;
+    if c {
+        a[i] = Some(1);
+        i = 1;
+    }
+    // `i` changes between setting `a[i]` and switching on its discriminant.
+    match a[i] {
+        Some(x) => x,
+        None => 0,
+    }
+}
+
+fn main() {
+    assert_eq!(question_marks(5), Ok(10));
+    assert_eq!(question_marks(-5), Err(Error::Negative));
+    assert_eq!(question_marks(500), Err(Error::TooLarge));
+    assert_eq!(option_then_match(8), 4);
+    assert_eq!(option_then_match(7), 255);
+    assert_eq!(bool_flag(15), 5);
+    assert_eq!(bool_flag(5), 5);
+    assert_eq!(in_loop(&[1, 2, 3]), Some(6));
+    assert_eq!(in_loop(&[1, -2, 3]), None);
+    assert_eq!(indexed_discriminant(true), 2);
+    assert_eq!(indexed_discriminant(false), 0);
+}
@@ -0,0 +1,62 @@
+// Checks that splitting tuple and struct locals into one local per field keeps
+// the behavior of the program.
+// run-pass
+// compile-flags: -Zmir-opt-level=3 -Zvalidate-mir
+
+#[derive(Clone, Copy)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+#[inline(never)]
+fn swap_fields(a: u8, b: u16) -> (u16, u8) {
+    let t = (a, b);
+    (t.1, t.0)
+}
+
+#[inline(never)]
+fn nested(a: i32, b: i32, c: i32) -> i32 {
+    let t = ((a, b), Point { x: c, y: a });
+    let u = (t.0).1 + t.1.x;
+    u * t.1.y
+}
+
+#[inline(never)]
+fn sum_pairs(v: &[(u32, u32)]) -> u32 {
+    let mut total = 0;
+    for &(a, b) in v {
+        let p = Pair { first: a, second: b };
+        total += p.first * p.second;
+    }
+    total
+}
+
+#[inline(never)]
+fn borrowed(a: i32) -> i32 {
+    let mut p = Point { x: a, y: 0 };
+    let r = &mut p;
+    r.y = 5;
+    p.x + p.y
+}
+
+#[inline(never)]
+fn with_drop(s: String) -> usize {
+    let t = (s, String::from("abc"));
+    let n = t.0.len();
+    drop(t.1);
+    n
+}
+
+fn main() {
+    assert_eq!(swap_fields(1, 2), (2, 1));
+    assert_eq!(nested(2, 3, 4), 14);
+    assert_eq!(sum_pairs(&[(1, 2), (3, 4)]), 14);
+    assert_eq!(borrowed(3), 8);
+    assert_eq!(with_drop(String::from("hello")), 5);
+}
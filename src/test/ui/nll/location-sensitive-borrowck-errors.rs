@@ -0,0 +1,22 @@
+// Checks that `-Z location-sensitive-borrowck` still rejects conflicts with loans that flow into
+// a universal region, through an out-parameter or a dead temporary, and with two-phase borrows
+// during their reservation.
+
+// ignore-compare-mode-nll
+// compile-flags: -Z borrowck=mir -Z location-sensitive-borrowck
+
+fn out_param<'a>(out: &mut &'a u32, x: &'a mut u32) {
+    *out = &*x;
+    *x = 1; //~ ERROR cannot assign to `*x` because it is borrowed
+}
+
+fn dead_temporary<'a>(out: &mut Vec<&'a u32>, x: &'a mut u32) {
+    out.push(&*x);
+    *x = 1; //~ ERROR cannot assign to `*x` because it is borrowed
+}
+
+fn two_phase(v: &mut Vec<u32>) {
+    v.push(v.pop().unwrap_or(0)); //~ ERROR cannot borrow `*v` as mutable more than once at a time
+}
+
+fn main() {}
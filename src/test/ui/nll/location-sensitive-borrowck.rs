@@ -0,0 +1,54 @@
+// Checks that `-Z location-sensitive-borrowck` accepts returning a borrow on some paths and
+// borrowing the same place again on the others ("NLL problem case #3"), and still rejects the
+// conflicts on the paths where the borrow is returned.
+
+// ignore-compare-mode-nll
+// compile-flags: -Z borrowck=mir -Z location-sensitive-borrowck
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+fn get_default<'r, K: Hash + Eq + Copy, V: Default>(
+    map: &'r mut HashMap<K, V>,
+    key: K,
+) -> &'r mut V {
+    match map.get_mut(&key) {
+        Some(value) => value,
+        None => {
+            map.insert(key, V::default());
+            map.get_mut(&key).unwrap()
+        }
+    }
+}
+
+struct Map {}
+
+impl Map {
+    fn get(&self) -> Option<&String> {
+        None
+    }
+    fn set(&mut self, _v: String) {}
+}
+
+fn in_loop(map: &mut Map) -> &String {
+    loop {
+        match map.get() {
+            Some(v) => return v,
+            None => map.set(String::new()),
+        }
+    }
+}
+
+fn conflict_before_return(map: &mut Map) -> &String {
+    loop {
+        match map.get() {
+            Some(v) => {
+                map.set(String::new()); //~ ERROR borrowed as immutable
+                return v;
+            }
+            None => map.set(String::new()),
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Checks that `--error-format=sarif` writes a single SARIF log to stderr, which
+// also contains the warnings about the command line emitted before the session
+// exists.
+
+// check-pass
+// compile-flags: --error-format=sarif -Z unstable-options -C remark=all
+// ignore-windows (absolute paths become `file:///C:/...` URIs)
+
+fn main() {
+    let unused = 1;
+}
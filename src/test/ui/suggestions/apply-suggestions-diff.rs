@@ -0,0 +1,10 @@
+// check-pass
+// compile-flags: -Z apply-suggestions=diff
+
+#![warn(unused_mut)]
+
+fn main() {
+    let mut x = 1; //~ WARN variable does not need to be mutable
+    let mut y = 2; //~ WARN variable does not need to be mutable
+    println!("{} {}", x, y);
+}